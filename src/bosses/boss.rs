@@ -10,13 +10,13 @@ use bevy::prelude::*;
 use crate::bosses::boss_health_bar::BossDamageEvent;
 use crate::game;
 use crate::movement_patterns::MovementPatterns::DontMovePattern;
-use crate::player::PlayerShot;
+use crate::player::{shot_hit_box, PlayerShot};
 
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Boss;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct BossSpawner {
     pub name: &'static str,
     pub enemy_type: EnemyType,
@@ -81,6 +81,13 @@ pub fn spawn_bosses(
     }
 }
 
+pub fn boss_hit_box(boss_sprite: &AnimatedSprite, boss_transform: &Transform) -> Aabb2d {
+    Aabb2d::new(
+        boss_transform.translation.truncate(),
+        boss_sprite.sprite_size.as_vec2().div(2.0),
+    )
+}
+
 pub fn check_boss_being_shot(
     mut commands: Commands,
     boss_query: Query<(&Transform, &AnimatedSprite), With<Boss>>,
@@ -88,16 +95,9 @@ pub fn check_boss_being_shot(
     shot_query: Query<(&PlayerShot, &Transform, &Sprite, Entity)>,
 ) {
     for (boss_transform, boss_sprite) in boss_query.iter() {
-        let boss_hit_box = Aabb2d::new(
-            boss_transform.translation.truncate(),
-            boss_sprite.sprite_size.as_vec2().div(2.0),
-        );
+        let boss_hit_box = boss_hit_box(boss_sprite, boss_transform);
         for (shot, shot_transform, shot_sprite, shot_entity) in shot_query.iter() {
-            let shot_hit_box = Aabb2d::new(
-                shot_transform.translation.truncate(),
-                shot_sprite.rect.unwrap().half_size(),
-            );
-            if boss_hit_box.intersects(&shot_hit_box) {
+            if boss_hit_box.intersects(&shot_hit_box(shot_sprite, shot_transform)) {
                 boss_damage_event_writer.send(BossDamageEvent(shot.damage));
                 commands.entity(shot_entity).try_despawn();
             }
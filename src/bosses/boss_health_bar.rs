@@ -1,20 +1,34 @@
 use bevy::asset::{Assets, Handle};
 use bevy::color::Color;
 use bevy::core::Name;
-use bevy::prelude::{Commands, Component, Entity, Event, EventReader, Mesh, Mesh2d, MeshMaterial2d, Query, Rectangle, ResMut, Transform, With};
+use bevy::prelude::{Commands, Component, Entity, Event, EventReader, EventWriter, Mesh, Mesh2d, MeshMaterial2d, Query, Rectangle, Res, ResMut, Time, Timer, TimerMode, Transform, With};
 use bevy::sprite::ColorMaterial;
-use crate::game::{FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
+use crate::game::{GameObject, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
 use crate::spawns::SPAWN_CENTER;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct BossHealthBar {
     pub current: i32,
     pub maximum: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct BossHealthBarBundle;
 
+pub const SPELL_TIME_LIMIT_SECONDS: f32 = 40.0;
+const SPELL_TIME_WARNING_SECONDS: u32 = 5;
+
+// a spell that isn't broken in time ends on its own. sits on the same entity as the spell's health bar
+#[derive(Component, Clone)]
+pub struct SpellTimeLimit(Timer);
+
+impl Default for SpellTimeLimit {
+    fn default() -> Self {
+        Self(Timer::from_seconds(SPELL_TIME_LIMIT_SECONDS, TimerMode::Once))
+    }
+}
+
 #[derive(Event)]
 pub struct BossDamageEvent(pub i32);
 
@@ -30,7 +44,8 @@ pub fn spawn_boss_health_bar(
         Mesh2d(bar_mesh_handle),
         MeshMaterial2d(materials.add(Color::hsl(1.0, 0.5, 0.5))),
         Transform::from_xyz(SPAWN_CENTER, FRAME_BORDER_TOP, 1.0),
-        BossHealthBarBundle
+        BossHealthBarBundle,
+        GameObject,
     ));
 }
 
@@ -66,4 +81,42 @@ pub fn listen_for_boss_damage(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// ticks once a second over the last few seconds, then empties the health bar so the spell ends
+pub fn count_down_spell_time_limit(
+    time: Res<Time>,
+    mut time_limit_query: Query<(&mut SpellTimeLimit, &mut BossHealthBar)>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    for (mut time_limit, mut health_bar) in time_limit_query.iter_mut() {
+        let seconds_before = time_limit.0.remaining_secs().ceil() as u32;
+        if time_limit.0.tick(time.delta()).just_finished() {
+            health_bar.current = 0;
+            continue;
+        }
+        let seconds_after = time_limit.0.remaining_secs().ceil() as u32;
+        if seconds_after < seconds_before && seconds_after <= SPELL_TIME_WARNING_SECONDS {
+            play_sound_event_writer.send(PlaySoundEvent(SoundEffect::TimerWarning));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bosses::rumia::RumiaState;
+    use crate::game::{Checkpoint, LevelState};
+    use crate::headless::{Harness, SECONDS};
+    use crate::launch_options::LaunchOptions;
+    use crate::level1::FirstLevelState;
+
+    #[test]
+    fn a_spell_left_unbroken_ends_when_its_time_runs_out() {
+        let mut harness = Harness::from_checkpoint(LevelState::Level1, Checkpoint::Spell(2), 1);
+        harness.world_mut().resource_mut::<LaunchOptions>().invincible = true;
+        assert!(harness.step_until(RumiaState::Spell2, 5 * SECONDS));
+
+        assert!(harness.step_until(FirstLevelState::PostRumia, (SPELL_TIME_LIMIT_SECONDS as usize + 5) * SECONDS));
+    }
+}
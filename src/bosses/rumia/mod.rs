@@ -6,12 +6,19 @@ use crate::bosses::boss_health_bar::{despawn_boss_health_bar, BossHealthBar};
 use crate::bosses::rumia::spell1::{spell1_plugin, Spell1State};
 use crate::bosses::rumia::spell2::{spell2_plugin, Spell2State};
 use crate::enemy::EnemyType::Rumia;
-use crate::game::{GameObject, SpawnTimer};
+use crate::game::{Checkpoint, ChosenLevel, GameObject, SpawnTimer, StageClearEvent};
 use crate::level1::FirstLevelState;
 use crate::movement_patterns::{is_finished, MovementPatterns};
+use crate::practice::PracticeSession;
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
+use crate::simulation::SimulationSet;
+use crate::snapshot::snapshot_state;
 use crate::spawns::{SPAWN_CENTER, SPAWN_TOP};
 use bevy::prelude::*;
 
+// the spell cards a checkpoint can start at, numbered from 1
+pub const SPELL_COUNT: u8 = 2;
+
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 pub enum RumiaState {
     #[default]
@@ -24,11 +31,16 @@ pub enum RumiaState {
 pub fn rumia_plugin(app: &mut App) {
     app
         .add_systems(OnEnter(FirstLevelState::Rumia), rumia_setup)
-        .add_systems(Update, rumia_orchestrator.run_if(in_state(FirstLevelState::Rumia)))
+        .add_systems(FixedUpdate, rumia_orchestrator
+            .run_if(in_state(FirstLevelState::Rumia))
+            .in_set(SimulationSet::Stage))
+        .add_systems(OnEnter(RumiaState::Spell1), declare_spell)
+        .add_systems(OnEnter(RumiaState::Spell2), declare_spell)
         .add_systems(OnEnter(RumiaState::Complete), transition_out_of_fight)
         .add_systems(OnEnter(RumiaState::Inactive), (rumia_cleanup, despawn_boss_health_bar))
         .add_plugins(spell1_plugin)
         .add_plugins(spell2_plugin)
+        .add_plugins(snapshot_state::<RumiaState>)
         .init_state::<RumiaState>()
     ;
 }
@@ -56,15 +68,26 @@ pub fn rumia_orchestrator(
     rumia_state: Res<State<RumiaState>>,
     mut rumia_next_state: ResMut<NextState<RumiaState>>,
     health_bar_query: Query<(&BossHealthBar, Entity)>,
+    chosen_level: Res<ChosenLevel>,
+    practice: Option<Res<PracticeSession>>,
 ) {
+    // practicing a spell card ends the fight as soon as that spell is broken
+    let practicing_spell = practice.is_some_and(|practice| practice.is_spell());
     for (_boss, movement_pattern) in boss_query.iter() {
         if *rumia_state.get() == RumiaState::Inactive && is_finished(movement_pattern) {
-            rumia_next_state.set(RumiaState::Spell1);
+            match chosen_level.checkpoint {
+                Checkpoint::Spell(2) => rumia_next_state.set(RumiaState::Spell2),
+                _ => rumia_next_state.set(RumiaState::Spell1),
+            }
         }
     }
     for (health_bar, entity) in health_bar_query.iter() {
         if health_bar.current <= 0 {
             match *rumia_state.get() {
+                RumiaState::Spell1 if practicing_spell => {
+                    rumia_next_state.set(RumiaState::Complete);
+                    commands.entity(entity).try_despawn();
+                },
                 RumiaState::Spell1 => {
                     rumia_next_state.set(RumiaState::Spell2);
                     commands.entity(entity).try_despawn();
@@ -79,11 +102,24 @@ pub fn rumia_orchestrator(
     }
 }
 
+fn declare_spell(
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    play_sound_event_writer.send(PlaySoundEvent(SoundEffect::SpellDeclare));
+}
+
 fn transition_out_of_fight(
     mut first_level_next_state: ResMut<NextState<FirstLevelState>>,
     mut rumia_next_state: ResMut<NextState<RumiaState>>,
+    mut stage_clear_event_writer: EventWriter<StageClearEvent>,
+    practice: Option<Res<PracticeSession>>,
 ) {
-    first_level_next_state.set(FirstLevelState::PostRumia);
+    if practice.is_some_and(|practice| practice.is_spell()) {
+        first_level_next_state.set(FirstLevelState::Cleared);
+        stage_clear_event_writer.send(StageClearEvent);
+    } else {
+        first_level_next_state.set(FirstLevelState::PostRumia);
+    }
     rumia_next_state.set(RumiaState::Inactive);
 }
 
@@ -102,4 +138,34 @@ pub fn rumia_cleanup(
     }
     spell1_state.set(Spell1State::Inactive);
     spell2_state.set(Spell2State::Inactive);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::LevelState;
+    use crate::headless::{holding, Harness, SECONDS};
+    use crate::input::GameButton;
+    use crate::launch_options::LaunchOptions;
+
+    #[test]
+    fn breaking_both_of_rumias_spells_moves_on_to_post_rumia() {
+        let mut harness = Harness::new(LevelState::Level1, 1);
+        harness.world_mut().resource_mut::<LaunchOptions>().invincible = true;
+        harness.hold(holding(GameButton::Shot));
+        assert!(harness.step_until(FirstLevelState::Rumia, 30 * SECONDS));
+
+        for _ in 0..60 * SECONDS {
+            if harness.state::<FirstLevelState>() == FirstLevelState::PostRumia {
+                break;
+            }
+            let world = harness.world_mut();
+            for mut health_bar in world.query::<&mut BossHealthBar>().iter_mut(world) {
+                health_bar.current = 0;
+            }
+            harness.step(1);
+        }
+
+        assert_eq!(harness.state::<FirstLevelState>(), FirstLevelState::PostRumia);
+    }
+}
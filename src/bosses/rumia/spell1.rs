@@ -1,5 +1,5 @@
 use crate::bosses::boss::{check_boss_being_shot, Boss};
-use crate::bosses::boss_health_bar::{despawn_boss_health_bar, listen_for_boss_damage, scale_boss_health_bar, spawn_boss_health_bar, BossHealthBar};
+use crate::bosses::boss_health_bar::{count_down_spell_time_limit, despawn_boss_health_bar, listen_for_boss_damage, scale_boss_health_bar, spawn_boss_health_bar, BossHealthBar, SpellTimeLimit};
 use crate::bosses::rumia::RumiaState;
 use crate::bullet::BulletType;
 use crate::bullet::BulletType::{BlueRimmedCircle, RedRimmedCircle};
@@ -7,15 +7,18 @@ use crate::bullet_patterns::shot_schedule::ShotSchedule;
 use crate::bullet_patterns::starburst::Starburst;
 use crate::bullet_patterns::BulletPattern::StarburstPattern;
 use crate::bullet_patterns::Target;
-use crate::game::{GameObject, LevelState, FRAME_BORDER_TOP};
-use crate::movement_patterns::decelerate::create_move_to_pattern;
+use crate::game::{Difficulty, GameObject, LevelState, FRAME_BORDER_TOP};
+use crate::movement_patterns::easing::Easing;
+use crate::movement_patterns::move_to::create_move_to_pattern;
 use crate::movement_patterns::{is_finished, MovementPatterns};
 use crate::resources::sprites::{set_one_off_animation, AnimationIndices};
+use crate::simulation::SimulationSet;
+use crate::snapshot::{snapshot_component, snapshot_state};
 use crate::spawns::{SPAWN_CENTER, SPAWN_TOP};
 use bevy::prelude::*;
 use std::time::Duration;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 struct SpellTimer(Timer);
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
@@ -33,34 +36,44 @@ pub enum Spell1State {
 pub fn spell1_plugin(app: &mut App) {
     app
         .add_systems(OnEnter(RumiaState::Spell1), (enter_spell1, spawn_boss_health_bar))
-        .add_systems(Update, (check_boss_being_shot, listen_for_boss_damage, scale_boss_health_bar)
+        .add_systems(FixedUpdate, (check_boss_being_shot, listen_for_boss_damage, count_down_spell_time_limit).chain()
+            .run_if(in_state(RumiaState::Spell1))
+            .in_set(SimulationSet::Stage))
+        .add_systems(Update, scale_boss_health_bar
             .run_if(in_state(RumiaState::Spell1)))
         .add_systems(OnEnter(Spell1State::Phase1), phase1_setup)
-        .add_systems(Update, phase1_countdown
-            .run_if(in_state(Spell1State::Phase1)))
+        .add_systems(FixedUpdate, phase1_countdown
+            .run_if(in_state(Spell1State::Phase1))
+            .in_set(SimulationSet::Stage))
 
         .add_systems(OnEnter(Spell1State::MoveToPhase2), move_to_phase2_setup)
-        .add_systems(Update, wait_for_move_to_phase2
-            .run_if(in_state(Spell1State::MoveToPhase2)))
+        .add_systems(FixedUpdate, wait_for_move_to_phase2
+            .run_if(in_state(Spell1State::MoveToPhase2))
+            .in_set(SimulationSet::Stage))
 
         .add_systems(OnEnter(Spell1State::Phase2), phase2_setup)
-        .add_systems(Update, phase2_countdown
-            .run_if(in_state(Spell1State::Phase2)))
+        .add_systems(FixedUpdate, phase2_countdown
+            .run_if(in_state(Spell1State::Phase2))
+            .in_set(SimulationSet::Stage))
 
         .add_systems(OnEnter(Spell1State::MoveToPhase3), move_to_phase3_setup)
-        .add_systems(Update, wait_for_move_to_phase3
-            .run_if(in_state(Spell1State::MoveToPhase3)))
+        .add_systems(FixedUpdate, wait_for_move_to_phase3
+            .run_if(in_state(Spell1State::MoveToPhase3))
+            .in_set(SimulationSet::Stage))
 
         .add_systems(OnEnter(Spell1State::Phase3), phase3_setup)
-        .add_systems(Update, phase3_countdown
-            .run_if(in_state(Spell1State::Phase3)))
+        .add_systems(FixedUpdate, phase3_countdown
+            .run_if(in_state(Spell1State::Phase3))
+            .in_set(SimulationSet::Stage))
 
         .add_systems(OnEnter(Spell1State::MoveToPhase1), move_to_phase1_setup)
-        .add_systems(Update, wait_for_move_to_phase1
-            .run_if(in_state(Spell1State::MoveToPhase1)))
+        .add_systems(FixedUpdate, wait_for_move_to_phase1
+            .run_if(in_state(Spell1State::MoveToPhase1))
+            .in_set(SimulationSet::Stage))
         .add_systems(OnEnter(Spell1State::Inactive), despawn_boss_health_bar)
         .add_systems(OnExit(LevelState::Level1), reset_spell1)
         .add_systems(OnExit(RumiaState::Spell1), reset_spell1)
+        .add_plugins((snapshot_state::<Spell1State>, snapshot_component::<SpellTimer>))
         .init_state::<Spell1State>()
     ;
 }
@@ -74,14 +87,17 @@ pub fn reset_spell1(
 fn enter_spell1(
     mut commands: Commands,
     mut state: ResMut<NextState<Spell1State>>,
+    difficulty: Res<Difficulty>,
 ) {
     state.set(Spell1State::MoveToPhase1);
+    let health = difficulty.pick([80, 100, 120, 140]);
     commands.spawn((
         Name::new("Rumia Spell 1 Health Bar"),
         BossHealthBar {
-            current: 100,
-            maximum: 100,
+            current: health,
+            maximum: health,
         },
+        SpellTimeLimit::default(),
         GameObject
     ));
 }
@@ -90,6 +106,7 @@ fn phase1_setup(
     mut commands: Commands,
     mut rumia_query: Query<(&Boss, &Transform, &mut AnimationIndices)>,
     mut state: ResMut<NextState<Spell1State>>,
+    difficulty: Res<Difficulty>,
 ) {
     for (_boss, boss_transform, mut animation_indices) in rumia_query.iter_mut() {
         set_one_off_animation(&mut animation_indices, 0, 3);
@@ -97,9 +114,9 @@ fn phase1_setup(
             Name::new("Phase 1 Blue Starburst Pattern"),
             StarburstPattern(
                 Starburst {
-                    bullets: vec![BlueRimmedCircle; 5],
-                    num_lines: 16,
-                    speed_range: (120.0, 200.0),
+                    bullets: vec![BlueRimmedCircle; difficulty.pick([3, 5, 6, 7])],
+                    num_lines: difficulty.pick([10, 16, 20, 24]),
+                    speed_range: difficulty.pick([(100.0, 160.0), (120.0, 200.0), (130.0, 220.0), (140.0, 240.0)]),
                     ..default()
                 },
                 Target::Player,
@@ -132,12 +149,11 @@ fn phase1_countdown(
 }
 
 fn move_to_phase2_setup(
-    mut rumia_query: Query<(&Boss, &Transform, &mut MovementPatterns)>,
+    mut rumia_query: Query<(&Boss, &mut MovementPatterns)>,
 ) {
-    for (_boss, transform, mut movement_pattern) in rumia_query.iter_mut() {
-        let start = transform.translation.xy();
+    for (_boss, mut movement_pattern) in rumia_query.iter_mut() {
         let destination = Vec2::new(SPAWN_CENTER, SPAWN_TOP - 100.0);
-        *movement_pattern = create_move_to_pattern(start, destination, Duration::from_millis(1500));
+        *movement_pattern = create_move_to_pattern(destination, Duration::from_millis(1500), Easing::EaseOut);
     }
 }
 
@@ -155,6 +171,7 @@ fn wait_for_move_to_phase2(
 fn phase2_setup(
     mut commands: Commands,
     mut rumia_query: Query<(&Boss, &Transform, &mut AnimationIndices)>,
+    difficulty: Res<Difficulty>,
 ) {
     for (_boss, boss_transform, mut animation_indices) in rumia_query.iter_mut() {
         set_one_off_animation(&mut animation_indices, 0, 3);
@@ -171,8 +188,8 @@ fn phase2_setup(
                 StarburstPattern(
                     Starburst {
                         bullets: vec![wave.0],
-                        num_lines: 64,
-                        speed_range: (120.0, 200.0),
+                        num_lines: difficulty.pick([40, 64, 72, 80]),
+                        speed_range: difficulty.pick([(100.0, 160.0), (120.0, 200.0), (130.0, 220.0), (140.0, 240.0)]),
                         offset: wave.1,
                         ..default()
                     },
@@ -210,12 +227,11 @@ fn phase2_countdown(
 }
 
 fn move_to_phase3_setup(
-    mut rumia_query: Query<(&Boss, &Transform, &mut MovementPatterns)>,
+    mut rumia_query: Query<(&Boss, &mut MovementPatterns)>,
 ) {
-    for (_boss, transform, mut movement_pattern) in rumia_query.iter_mut() {
-        let start = transform.translation.xy();
+    for (_boss, mut movement_pattern) in rumia_query.iter_mut() {
         let destination = Vec2::new(SPAWN_CENTER - 150.0, FRAME_BORDER_TOP - 100.0);
-        *movement_pattern = create_move_to_pattern(start, destination, Duration::from_millis(1500));
+        *movement_pattern = create_move_to_pattern(destination, Duration::from_millis(1500), Easing::EaseOut);
     }
 }
 
@@ -233,6 +249,7 @@ fn wait_for_move_to_phase3(
 fn phase3_setup(
     mut commands: Commands,
     mut rumia_query: Query<(&Boss, &Transform, &mut AnimationIndices)>,
+    difficulty: Res<Difficulty>,
 ) {
     for (_boss, boss_transform, mut animation_indices) in rumia_query.iter_mut() {
         set_one_off_animation(&mut animation_indices, 0, 3);
@@ -240,9 +257,9 @@ fn phase3_setup(
             Name::new("Phase 1 Red Starburst Pattern"),
             StarburstPattern(
                 Starburst {
-                    bullets: vec![RedRimmedCircle; 5],
-                    num_lines: 16,
-                    speed_range: (120.0, 200.0),
+                    bullets: vec![RedRimmedCircle; difficulty.pick([3, 5, 6, 7])],
+                    num_lines: difficulty.pick([10, 16, 20, 24]),
+                    speed_range: difficulty.pick([(100.0, 160.0), (120.0, 200.0), (130.0, 220.0), (140.0, 240.0)]),
                     ..default()
                 },
                 Target::Player,
@@ -274,12 +291,11 @@ fn phase3_countdown(
 }
 
 fn move_to_phase1_setup(
-    mut rumia_query: Query<(&Boss, &Transform, &mut MovementPatterns)>,
+    mut rumia_query: Query<(&Boss, &mut MovementPatterns)>,
 ) {
-    for (_boss, transform, mut movement_pattern) in rumia_query.iter_mut() {
-        let start = transform.translation.xy();
+    for (_boss, mut movement_pattern) in rumia_query.iter_mut() {
         let destination = Vec2::new(SPAWN_CENTER + 150.0, FRAME_BORDER_TOP - 100.0);
-        *movement_pattern = create_move_to_pattern(start, destination, Duration::from_millis(1500));
+        *movement_pattern = create_move_to_pattern(destination, Duration::from_millis(1500), Easing::EaseOut);
     }
 }
 
@@ -1,5 +1,5 @@
 use crate::bosses::boss::{check_boss_being_shot, Boss};
-use crate::bosses::boss_health_bar::{listen_for_boss_damage, scale_boss_health_bar, spawn_boss_health_bar, BossHealthBar};
+use crate::bosses::boss_health_bar::{count_down_spell_time_limit, listen_for_boss_damage, scale_boss_health_bar, spawn_boss_health_bar, BossHealthBar, SpellTimeLimit};
 use crate::bosses::rumia::RumiaState;
 use crate::bullet::BulletType::{BlueRimmedCircle, RedRimmedCircle};
 use crate::bullet_patterns::shot_schedule::create_shot_schedule;
@@ -7,14 +7,17 @@ use crate::bullet_patterns::shotgun::Shotgun;
 use crate::bullet_patterns::starburst::Starburst;
 use crate::bullet_patterns::BulletPattern::{ShotgunPattern, StarburstPattern};
 use crate::bullet_patterns::{Target, ENDLESS};
-use crate::game::{GameObject, LevelState};
-use crate::movement_patterns::decelerate::create_move_to_pattern;
+use crate::game::{Difficulty, GameObject, LevelState};
+use crate::movement_patterns::easing::Easing;
+use crate::movement_patterns::move_to::create_move_to_pattern;
 use crate::movement_patterns::{is_finished, MovementPatterns};
 use crate::resources::sprites::{set_one_off_animation, AnimationIndices};
+use crate::simulation::SimulationSet;
+use crate::snapshot::{snapshot_component, snapshot_state};
 use crate::spawns::{SPAWN_CENTER, SPAWN_TOP};
 use bevy::app::App;
-use bevy::math::{Vec2, Vec3Swizzles};
-use bevy::prelude::{default, in_state, AppExtStates, Commands, Component, Entity, IntoSystemConfigs, NextState, OnEnter, OnExit, Query, ResMut, States, Transform, Update, With};
+use bevy::math::Vec2;
+use bevy::prelude::{default, in_state, AppExtStates, Commands, Component, Entity, FixedUpdate, IntoSystemConfigs, NextState, OnEnter, OnExit, Query, Res, ResMut, States, Transform, Update, With};
 use std::f32::consts::PI;
 use std::time::Duration;
 use bevy::core::Name;
@@ -28,21 +31,26 @@ pub enum Spell2State {
     Phase1,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 struct RumiaSpell2Object;
 
 pub fn spell2_plugin(app: &mut App) {
     app
         .add_systems(OnEnter(RumiaState::Spell2), enter_spell2)
-        .add_systems(Update, (check_boss_being_shot, listen_for_boss_damage, scale_boss_health_bar)
+        .add_systems(FixedUpdate, (check_boss_being_shot, listen_for_boss_damage, count_down_spell_time_limit).chain()
+            .run_if(in_state(RumiaState::Spell2))
+            .in_set(SimulationSet::Stage))
+        .add_systems(Update, scale_boss_health_bar
             .run_if(in_state(RumiaState::Spell2)))
-        .add_systems(Update, wait_for_move_to_phase1
-            .run_if(in_state(Spell2State::MoveToPhase1)))
+        .add_systems(FixedUpdate, wait_for_move_to_phase1
+            .run_if(in_state(Spell2State::MoveToPhase1))
+            .in_set(SimulationSet::Stage))
 
         .add_systems(OnEnter(Spell2State::Phase1), (phase1_setup, spawn_boss_health_bar))
 
         .add_systems(OnExit(LevelState::Level1), reset_spell2)
         .add_systems(OnExit(RumiaState::Spell2), reset_spell2)
+        .add_plugins((snapshot_state::<Spell2State>, snapshot_component::<RumiaSpell2Object>))
         .init_state::<Spell2State>()
     ;
 }
@@ -63,13 +71,12 @@ pub fn reset_spell2(
 }
 
 fn enter_spell2(
-    mut rumia_query: Query<(&Boss, &Transform, &mut MovementPatterns)>,
+    mut rumia_query: Query<(&Boss, &mut MovementPatterns)>,
     mut next_state: ResMut<NextState<Spell2State>>,
 ) {
-    for (_boss, transform, mut movement_pattern) in rumia_query.iter_mut() {
-        let start = transform.translation.xy();
+    for (_boss, mut movement_pattern) in rumia_query.iter_mut() {
         let destination = Vec2::new(SPAWN_CENTER, SPAWN_TOP - 150.0);
-        *movement_pattern = create_move_to_pattern(start, destination, Duration::from_millis(1500));
+        *movement_pattern = create_move_to_pattern(destination, Duration::from_millis(1500), Easing::EaseOut);
     }
     next_state.set(Spell2State::MoveToPhase1);
 }
@@ -87,19 +94,21 @@ fn wait_for_move_to_phase1(
 fn phase1_setup(
     mut commands: Commands,
     mut rumia_query: Query<(&Boss, &Transform, &mut AnimationIndices)>,
+    difficulty: Res<Difficulty>,
 ) {
+    let health = difficulty.pick([80, 100, 120, 140]);
     for (_boss, boss_transform, mut animation_indices) in rumia_query.iter_mut() {
         set_one_off_animation(&mut animation_indices, 0, 3);
         commands.spawn((
             Name::new("Phase 2 Shotgun Pattern"),
             ShotgunPattern(
                 Shotgun {
-                    bullets: vec![RedRimmedCircle; 25],
+                    bullets: vec![RedRimmedCircle; difficulty.pick([15, 25, 30, 36])],
                     spread: PI / 8.0,
-                    speed_range: (200.0, 300.0),
+                    speed_range: difficulty.pick([(160.0, 240.0), (200.0, 300.0), (220.0, 330.0), (240.0, 360.0)]),
                 },
                 Target::Player,
-                create_shot_schedule(1.5, 1.5, ENDLESS),
+                create_shot_schedule(1.5, difficulty.pick([2.0, 1.5, 1.2, 1.0]), ENDLESS),
             ),
             Transform::from_translation(boss_transform.translation),
             GameObject,
@@ -109,13 +118,13 @@ fn phase1_setup(
             Name::new("Phase 2 Starburst Pattern"),
             StarburstPattern(
                 Starburst {
-                    bullets: vec![BlueRimmedCircle; 5],
-                    num_lines: 48,
-                    speed_range: (50.0, 250.0),
+                    bullets: vec![BlueRimmedCircle; difficulty.pick([4, 5, 6, 7])],
+                    num_lines: difficulty.pick([32, 48, 56, 64]),
+                    speed_range: difficulty.pick([(50.0, 200.0), (50.0, 250.0), (60.0, 280.0), (70.0, 300.0)]),
                     ..default()
                 },
                 Target::Down,
-                create_shot_schedule(0.0, 5.0, ENDLESS),
+                create_shot_schedule(0.0, difficulty.pick([6.0, 5.0, 4.0, 3.5]), ENDLESS),
             ),
             Transform::from_translation(boss_transform.translation),
             GameObject,
@@ -124,9 +133,10 @@ fn phase1_setup(
         commands.spawn((
             Name::new("Rumia Spell 2 Health Bar"),
             BossHealthBar {
-                current: 100,
-                maximum: 100,
+                current: health,
+                maximum: health,
             },
+            SpellTimeLimit::default(),
             GameObject,
             RumiaSpell2Object,
         ));
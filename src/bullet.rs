@@ -1,12 +1,16 @@
 use crate::bullet_patterns::{fire_bullet_pattern, BulletPattern};
 use crate::game::{is_in_playfield, GameObject, SpawnTimer};
 use crate::movement_patterns::MovementPatterns::DontMovePattern;
-use crate::movement_patterns::{run_movement_pattern, DontMove, MovementPatterns};
+use crate::movement_patterns::{get_movement_step, run_movement_pattern, set_homing_target, DontMove, MovementPatterns};
+use crate::laser::LaserSpawnEvent;
 use crate::player::Player;
 use crate::resources::sprites::Sprites;
+use crate::rank::Rank;
+use crate::rng::GameRng;
+use bevy::math::bounding::BoundingCircle;
 use bevy::prelude::*;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Bullet {
     pub bullet_type: BulletType,
 }
@@ -27,7 +31,7 @@ pub enum BulletType {
     SmallBlueCircle,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct BulletSpawner {
     pub bullet_type: BulletType,
     pub position: Vec2,
@@ -95,6 +99,10 @@ fn spawn_bullet(sprites: &Res<Sprites>, commands: &mut Commands, spawner: &Bulle
     ));
 }
 
+pub fn bullet_hit_circle(bullet: &Bullet, bullet_transform: &Transform) -> BoundingCircle {
+    BoundingCircle::new(bullet_transform.translation.truncate(), props_for_bullet_type(&bullet.bullet_type).hit_circle_radius)
+}
+
 pub fn props_for_bullet_type(_bullet_type: &BulletType) -> BulletProps {
     BulletProps {
         hit_circle_radius: 1.0,
@@ -103,9 +111,12 @@ pub fn props_for_bullet_type(_bullet_type: &BulletType) -> BulletProps {
 
 pub fn move_bullets(
     time: Res<Time>,
+    player_query: Query<&Transform, (With<Player>, Without<Bullet>)>,
     mut bullet_query: Query<(&Bullet, &mut Transform, &mut MovementPatterns)>,
 ) {
+    let player_position = player_query.iter().next().map(|transform| transform.translation.truncate());
     for (_bullet, mut transform, mut movement_pattern) in bullet_query.iter_mut() {
+        set_homing_target(&mut movement_pattern, player_position);
         run_movement_pattern(&mut movement_pattern, &mut transform, &time, true)
     }
 }
@@ -126,12 +137,16 @@ fn sprite_for_bullet_type(bullet_type: &BulletType, sprites: &Sprites) -> Sprite
 pub fn fire_bullet_patterns(
     time: Res<Time>,
     player_transform_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<(&mut BulletPattern, &mut Transform), Without<Player>>,
+    mut enemy_query: Query<(&mut BulletPattern, &mut Transform, Option<&MovementPatterns>), Without<Player>>,
     mut bullet_spawn_events: EventWriter<BulletSpawnEvent>,
+    mut laser_spawn_events: EventWriter<LaserSpawnEvent>,
+    mut rng: ResMut<GameRng>,
+    rank: Res<Rank>,
 ) {
     for (player_transform) in player_transform_query.iter() {
-        for (mut bullet_pattern, transform) in enemy_query.iter_mut() {
-            fire_bullet_pattern(&mut bullet_pattern, &time, &transform, &player_transform, &mut bullet_spawn_events);
+        for (mut bullet_pattern, transform, movement_pattern) in enemy_query.iter_mut() {
+            let movement_step = movement_pattern.and_then(get_movement_step);
+            fire_bullet_pattern(&mut bullet_pattern, &time, &transform, &player_transform, movement_step, &rank, &mut rng.gameplay, &mut bullet_spawn_events, &mut laser_spawn_events);
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+use crate::laser::{LaserSpawnEvent, LaserType};
+use bevy::prelude::*;
+
+#[derive(Clone)]
+pub struct LaserShot {
+    pub laser_type: LaserType,
+    pub color: Color,
+    pub width: f32,
+    pub num_lines: usize,
+    pub spread: f32,
+}
+
+impl LaserShot {
+    pub fn fire(&self, origin: &Transform, angle: Rot2, laser_spawn_events: &mut EventWriter<LaserSpawnEvent>) {
+        let num_lines = self.num_lines.max(1);
+        let (first_angle, step_size) = if num_lines > 1 {
+            (angle.as_radians() - self.spread / 2.0, self.spread / (num_lines - 1) as f32)
+        } else {
+            (angle.as_radians(), 0.0)
+        };
+        for i in 0..num_lines {
+            let line_angle = Rot2::radians(first_angle + i as f32 * step_size);
+            laser_spawn_events.send(LaserSpawnEvent {
+                laser_type: self.laser_type.aimed(origin.translation.truncate(), line_angle),
+                color: self.color,
+                width: self.width,
+            });
+        }
+    }
+}
@@ -2,27 +2,33 @@ pub mod starburst;
 pub mod single_shot;
 pub mod shot_schedule;
 pub mod shotgun;
+pub mod laser_shot;
 
 use bevy::math::Rot2;
 use crate::bullet::BulletSpawnEvent;
 use crate::bullet_patterns::single_shot::SingleShot;
 use crate::bullet_patterns::shotgun::Shotgun;
 use crate::bullet_patterns::starburst::Starburst;
-use crate::bullet_patterns::BulletPattern::{SingleShotPattern, ShotgunPattern, StarburstPattern};
+use crate::bullet_patterns::BulletPattern::{LaserPattern, SingleShotPattern, ShotgunPattern, StarburstPattern};
+use crate::bullet_patterns::laser_shot::LaserShot;
+use crate::laser::LaserSpawnEvent;
+use rand_chacha::ChaCha8Rng;
 use bevy::prelude::{Component, EventWriter, Res, Time, Transform};
 use shot_schedule::ShotSchedule;
 use crate::game::angle_to_transform;
+use crate::rank::Rank;
 
 pub const ENDLESS: i32 = -1;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub enum BulletPattern {
     SingleShotPattern(SingleShot, Target, ShotSchedule),
     StarburstPattern(Starburst, Target, ShotSchedule),
     ShotgunPattern(Shotgun, Target, ShotSchedule),
+    LaserPattern(LaserShot, Target, ShotSchedule),
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub enum Target {
     Player,
     Down,
@@ -44,33 +50,45 @@ pub fn fire_bullet_pattern(
     time: &Res<Time>,
     origin: &Transform,
     player_transform: &Transform,
+    movement_step: Option<usize>,
+    rank: &Rank,
+    rng: &mut ChaCha8Rng,
     bullet_spawn_events: &mut EventWriter<BulletSpawnEvent>,
+    laser_spawn_events: &mut EventWriter<LaserSpawnEvent>,
 ) {
     match bullet_pattern {
         SingleShotPattern(shoot_at_player, target, shot_schedule) => {
             let angle = target.get_angle(origin, player_transform);
-            let fire = || shoot_at_player.fire(origin, angle, bullet_spawn_events);
-            run_schedule(fire, shot_schedule, time);
+            let fire = || shoot_at_player.fire(origin, angle, rank, bullet_spawn_events);
+            run_schedule(fire, shot_schedule, movement_step, time);
         }
         StarburstPattern(starburst, target, shot_schedule) => {
             let angle = target.get_angle(origin, player_transform);
-            let fire = || starburst.fire(origin, angle, bullet_spawn_events);
-            run_schedule(fire, shot_schedule, time);
+            let fire = || starburst.fire(origin, angle, rank, bullet_spawn_events);
+            run_schedule(fire, shot_schedule, movement_step, time);
         }
         ShotgunPattern(shotgun, target, shot_schedule) => {
             let angle = target.get_angle(origin, player_transform);
-            let fire = || shotgun.fire(origin, angle, bullet_spawn_events);
-            run_schedule(fire, shot_schedule, time);
+            let fire = || shotgun.fire(origin, angle, rank, rng, bullet_spawn_events);
+            run_schedule(fire, shot_schedule, movement_step, time);
+        }
+        LaserPattern(laser_shot, target, shot_schedule) => {
+            let angle = target.get_angle(origin, player_transform);
+            let fire = || laser_shot.fire(origin, angle, laser_spawn_events);
+            run_schedule(fire, shot_schedule, movement_step, time);
         }
     }
 }
 
-fn run_schedule<F>(mut fire: F, shot_schedule: &mut ShotSchedule, time: &Res<Time>)
+fn run_schedule<F>(mut fire: F, shot_schedule: &mut ShotSchedule, movement_step: Option<usize>, time: &Res<Time>)
 where F: FnMut()
 {
+    if shot_schedule.movement_step.is_some_and(|step| movement_step != Some(step)) {
+        return;
+    }
     if shot_schedule.delay.tick(time.delta()).just_finished() {
         fire();
-    } else if shot_schedule.delay.tick(time.delta()).finished() {
+    } else if shot_schedule.delay.finished() {
         if shot_schedule.repetitions != 0 {
             if shot_schedule.interval.tick(time.delta()).just_finished() {
                 fire();
@@ -82,3 +100,50 @@ where F: FnMut()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use super::*;
+    use crate::bullet_patterns::shot_schedule::create_shot_schedule;
+    use crate::movement_patterns::decelerate::create_decelerate_pattern;
+    use crate::movement_patterns::sequence::{create_sequence_pattern, step, timed_step};
+    use crate::movement_patterns::straight_line::create_straight_line_pattern;
+    use crate::movement_patterns::MovementPatterns::DontMovePattern;
+    use crate::movement_patterns::{get_movement_step, run_movement_pattern, DontMove};
+
+    #[test]
+    fn a_schedule_gated_on_a_movement_step_only_fires_during_that_step() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(100));
+        world.insert_resource(time);
+
+        let (ticks_per_step, shots_per_step) = world.run_system_once(|time: Res<Time>| {
+            // enter, hover and fire, then leave
+            let mut movement_pattern = create_sequence_pattern(vec![
+                step(create_decelerate_pattern(Rot2::degrees(270.0), 400.0, 20.0, Duration::from_secs(1))),
+                timed_step(DontMovePattern(DontMove), 1.0),
+                step(create_straight_line_pattern(Rot2::degrees(90.0), 150.0)),
+            ], false);
+            let mut shot_schedule = create_shot_schedule(0.0, 0.25, ENDLESS).during_movement_step(1);
+            let mut transform = Transform::default();
+            let mut ticks_per_step = [0; 3];
+            let mut shots_per_step = [0; 3];
+            for _ in 0..40 {
+                let movement_step = get_movement_step(&movement_pattern).unwrap();
+                run_schedule(|| shots_per_step[movement_step] += 1, &mut shot_schedule, Some(movement_step), &time);
+                ticks_per_step[movement_step] += 1;
+                run_movement_pattern(&mut movement_pattern, &mut transform, &time, false);
+            }
+            (ticks_per_step, shots_per_step)
+        }).unwrap();
+
+        assert!(ticks_per_step.iter().all(|ticks| *ticks > 0), "{:?}", ticks_per_step);
+        assert_eq!(shots_per_step[0], 0);
+        assert!(shots_per_step[1] > 1);
+        assert_eq!(shots_per_step[2], 0);
+    }
+}
@@ -1,10 +1,19 @@
 use bevy::prelude::Timer;
 use bevy::time::TimerMode;
 
+#[derive(Clone)]
 pub struct ShotSchedule {
     pub delay: Timer,
     pub interval: Timer,
     pub repetitions: i32,
+    pub movement_step: Option<usize>,
+}
+
+impl ShotSchedule {
+    pub fn during_movement_step(mut self, movement_step: usize) -> Self {
+        self.movement_step = Some(movement_step);
+        self
+    }
 }
 
 impl Default for ShotSchedule {
@@ -13,6 +22,7 @@ impl Default for ShotSchedule {
             delay: Timer::default(),
             interval: Timer::default(),
             repetitions: 1,
+            movement_step: None,
         }
     }
 }
@@ -22,5 +32,6 @@ pub fn create_shot_schedule(delay: f32, interval: f32, repetitions: i32) -> Shot
         delay: Timer::from_seconds(delay, TimerMode::Once),
         interval: Timer::from_seconds(interval, TimerMode::Once),
         repetitions,
+        movement_step: None,
     }
 }
\ No newline at end of file
@@ -2,10 +2,13 @@ use bevy::math::Rot2;
 use bevy::prelude::{EventWriter, ResMut, Transform};
 use crate::bullet::{BulletSpawnEvent, BulletType};
 use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use crate::bullet_patterns::{BulletPattern, Target};
 use crate::bullet_patterns::BulletPattern::ShotgunPattern;
 use crate::movement_patterns::straight_line::create_straight_line_pattern;
+use crate::rank::Rank;
 
+#[derive(Clone)]
 pub struct Shotgun {
     pub bullets: Vec<BulletType>,
     pub spread: f32,
@@ -27,15 +30,18 @@ impl Shotgun {
         &self,
         origin: &Transform,
         angle: Rot2,
+        rank: &Rank,
+        rng: &mut ChaCha8Rng,
         bullet_spawn_events: &mut EventWriter<BulletSpawnEvent>,
     ) {
-        let mut rng = rand::rng();
-        for bullet_type in &self.bullets {
+        // extra bullets from rank cycle through the pattern's own bullet types
+        let bullet_count = rank.scale_count(self.bullets.len());
+        for bullet_type in self.bullets.iter().cycle().take(bullet_count) {
             let spread_limit_left = angle.as_radians() - self.spread / 2.0;
             let spread_limit_right = angle.as_radians() + self.spread / 2.0;
             let direction = rng.random_range(spread_limit_left..spread_limit_right);
 
-            let speed = rng.random_range(self.speed_range.0..self.speed_range.1);
+            let speed = rng.random_range(self.speed_range.0..self.speed_range.1) * rank.speed_scale();
             bullet_spawn_events.send(BulletSpawnEvent {
                 bullet_type: *bullet_type,
                 position: origin.translation.truncate(),
@@ -7,7 +7,9 @@ use bevy::math::Rot2;
 use bevy::prelude::{default, EventWriter, Timer, TimerMode, Transform};
 use crate::game::angle_to_transform;
 use crate::movement_patterns::straight_line::create_straight_line_pattern;
+use crate::rank::Rank;
 
+#[derive(Clone)]
 pub struct SingleShot {
     pub bullet_type: BulletType,
     pub speed: f32
@@ -28,12 +30,13 @@ impl SingleShot {
         &self,
         origin: &Transform,
         angle: Rot2,
+        rank: &Rank,
         bullet_spawn_events: &mut EventWriter<BulletSpawnEvent>,
     ) {
         bullet_spawn_events.send(BulletSpawnEvent {
             bullet_type: self.bullet_type,
             position: origin.translation.truncate(),
-            movement_pattern: create_straight_line_pattern(angle, self.speed),
+            movement_pattern: create_straight_line_pattern(angle, self.speed * rank.speed_scale()),
             ..default()
         });
     }
@@ -3,7 +3,9 @@ use crate::movement_patterns::MovementPatterns::StraightLinePattern;
 use bevy::prelude::*;
 use std::f32::consts::PI;
 use crate::movement_patterns::straight_line::create_straight_line_pattern;
+use crate::rank::Rank;
 
+#[derive(Clone)]
 pub struct Starburst {
     pub bullets: Vec<BulletType>,
     pub num_lines: usize,
@@ -27,13 +29,15 @@ impl Default for Starburst {
 
 impl Starburst {
 
-    pub fn fire(&self, origin: &Transform, angle: Rot2, bullet_spawn_events: &mut EventWriter<BulletSpawnEvent>) {
-        let speed_increment = (self.speed_range.1 - self.speed_range.0) / self.bullets.len() as f32;
+    pub fn fire(&self, origin: &Transform, angle: Rot2, rank: &Rank, bullet_spawn_events: &mut EventWriter<BulletSpawnEvent>) {
+        let speed_range = (self.speed_range.0 * rank.speed_scale(), self.speed_range.1 * rank.speed_scale());
+        let speed_increment = (speed_range.1 - speed_range.0) / self.bullets.len() as f32;
         let speeds = (0..self.bullets.len()).map(|i| {
-            self.speed_range.0 + (i as f32 * speed_increment)
+            speed_range.0 + (i as f32 * speed_increment)
         }).collect::<Vec<f32>>();
-        let step_size = self.spread / self.num_lines as f32;
-        let angles = (0..self.num_lines + 1).map(|i: usize| {
+        let num_lines = rank.scale_count(self.num_lines);
+        let step_size = self.spread / num_lines as f32;
+        let angles = (0..num_lines + 1).map(|i: usize| {
             angle.as_radians() - PI + (i as f32 * step_size) + self.offset
         }).collect::<Vec<f32>>();
         for (bullet_type, speed) in self.bullets.iter()
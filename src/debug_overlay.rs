@@ -0,0 +1,172 @@
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::math::bounding::BoundingVolume;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use crate::bosses::boss::{boss_hit_box, Boss};
+use crate::bosses::rumia::spell1::Spell1State;
+use crate::bosses::rumia::spell2::Spell2State;
+use crate::bosses::rumia::RumiaState;
+use crate::bullet::{bullet_hit_circle, Bullet};
+use crate::enemy::{enemy_hit_circle, Enemy};
+use crate::game::{LevelState, StageClock, FRAME_BORDER_LEFT, FRAME_BORDER_TOP};
+use crate::laser::Laser;
+use crate::level1::FirstLevelState;
+use crate::player::{player_hit_circle, shot_hit_box, Player, PlayerShot};
+use crate::rank::Rank;
+use crate::resources::sprites::AnimatedSprite;
+use crate::GameState;
+
+const PLAYER_HITBOX_COLOR: Color = Color::srgb(1.0, 1.0, 1.0);
+const GRAZE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.3);
+const ENEMY_HITBOX_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+const SHOT_HITBOX_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+const BULLET_HITBOX_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+
+#[derive(Resource, Default)]
+struct DebugOverlay {
+    visible: bool,
+}
+
+#[derive(Component)]
+struct DebugOverlayText;
+
+pub fn debug_overlay_plugin(app: &mut App) {
+    if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+        app.add_plugins(FrameTimeDiagnosticsPlugin);
+    }
+    app
+        .init_resource::<DebugOverlay>()
+        .add_systems(Startup, spawn_debug_overlay_text)
+        .add_systems(Update, toggle_debug_overlay)
+        .add_systems(Update, (
+            draw_hitboxes,
+            update_debug_overlay_text,
+        ).run_if(|overlay: Res<DebugOverlay>| overlay.visible))
+    ;
+}
+
+fn spawn_debug_overlay_text(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        Name::new("DebugOverlayText"),
+        Text2d::new(""),
+        TextFont {
+            font: asset_server.load("fonts/Super-Cartoon.ttf"),
+            font_size: 12.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Left),
+        Anchor::TopLeft,
+        Transform::from_xyz(FRAME_BORDER_LEFT + 4.0, FRAME_BORDER_TOP - 4.0, 50.0),
+        Visibility::Hidden,
+        DebugOverlayText,
+    ));
+}
+
+fn toggle_debug_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut text_query: Query<&mut Visibility, With<DebugOverlayText>>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+        for mut visibility in text_query.iter_mut() {
+            *visibility = if overlay.visible { Visibility::Visible } else { Visibility::Hidden };
+        }
+    }
+}
+
+fn draw_hitboxes(
+    mut gizmos: Gizmos,
+    player_query: Query<(&Player, &Transform)>,
+    enemy_query: Query<(&AnimatedSprite, &Transform), With<Enemy>>,
+    boss_query: Query<(&AnimatedSprite, &Transform), With<Boss>>,
+    shot_query: Query<(&Sprite, &Transform), With<PlayerShot>>,
+    bullet_query: Query<(&Bullet, &Transform)>,
+    laser_query: Query<&Laser>,
+) {
+    for (player, transform) in player_query.iter() {
+        let hit_circle = player_hit_circle(player, transform);
+        gizmos.circle_2d(hit_circle.center, hit_circle.radius(), PLAYER_HITBOX_COLOR);
+        gizmos.circle_2d(hit_circle.center, player.graze_radius, GRAZE_COLOR);
+    }
+    for (sprite, transform) in enemy_query.iter() {
+        let hit_circle = enemy_hit_circle(sprite, transform);
+        gizmos.circle_2d(hit_circle.center, hit_circle.radius(), ENEMY_HITBOX_COLOR);
+    }
+    for (sprite, transform) in boss_query.iter() {
+        let hit_box = boss_hit_box(sprite, transform);
+        gizmos.rect_2d(hit_box.center(), hit_box.half_size() * 2.0, ENEMY_HITBOX_COLOR);
+    }
+    for (sprite, transform) in shot_query.iter() {
+        let hit_box = shot_hit_box(sprite, transform);
+        gizmos.rect_2d(hit_box.center(), hit_box.half_size() * 2.0, SHOT_HITBOX_COLOR);
+    }
+    for (bullet, transform) in bullet_query.iter() {
+        let hit_circle = bullet_hit_circle(bullet, transform);
+        gizmos.circle_2d(hit_circle.center, hit_circle.radius(), BULLET_HITBOX_COLOR);
+    }
+    for laser in laser_query.iter() {
+        let half_width = laser.hit_width() / 2.0;
+        if half_width <= 0.0 {
+            continue;
+        }
+        for (start, end) in laser.segments() {
+            let offset = (end - start).normalize_or_zero().perp() * half_width;
+            gizmos.line_2d(start + offset, end + offset, BULLET_HITBOX_COLOR);
+            gizmos.line_2d(start - offset, end - offset, BULLET_HITBOX_COLOR);
+        }
+    }
+}
+
+type DebugStates<'w> = (
+    Res<'w, State<GameState>>,
+    Res<'w, State<LevelState>>,
+    Res<'w, State<FirstLevelState>>,
+    Res<'w, State<RumiaState>>,
+    Res<'w, State<Spell1State>>,
+    Res<'w, State<Spell2State>>,
+);
+
+type CategoryFlags = (Has<Player>, Has<PlayerShot>, Has<Enemy>, Has<Boss>, Has<Bullet>, Has<Laser>);
+
+fn update_debug_overlay_text(
+    diagnostics: Res<DiagnosticsStore>,
+    stage_clock: Res<StageClock>,
+    rank: Res<Rank>,
+    (game_state, level_state, first_level_state, rumia_state, spell1_state, spell2_state): DebugStates,
+    entity_counts: Query<CategoryFlags>,
+    mut text_query: Query<&mut Text2d, With<DebugOverlayText>>,
+) {
+    let fps = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS).and_then(|fps| fps.smoothed()).unwrap_or_default();
+    let frame_time = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME).and_then(|frame_time| frame_time.smoothed()).unwrap_or_default();
+
+    let mut counts = [0; 6];
+    for (player, shot, enemy, boss, bullet, laser) in entity_counts.iter() {
+        for (count, is_category) in counts.iter_mut().zip([player, shot, enemy, boss, bullet, laser]) {
+            *count += is_category as usize;
+        }
+    }
+    let [players, shots, enemies, bosses, bullets, lasers] = counts;
+
+    let rank = if rank.enabled { format!("rank {:.3}", rank.value) } else { "rank off".to_string() };
+
+    for mut text in text_query.iter_mut() {
+        text.0 = format!(
+            "{:.0} fps ({:.2} ms)\n\
+            clock {:.2}s {}\n\
+            {:?} / {:?} / {:?}\n\
+            Rumia {:?} / {:?} / {:?}\n\
+            player {} shots {} enemies {} bosses {}\n\
+            bullets {} lasers {}",
+            fps, frame_time,
+            stage_clock.0.as_secs_f32(), rank,
+            game_state.get(), level_state.get(), first_level_state.get(),
+            rumia_state.get(), spell1_state.get(), spell2_state.get(),
+            players, shots, enemies, bosses,
+            bullets, lasers,
+        );
+    }
+}
@@ -3,7 +3,8 @@ use crate::enemy::{EnemyDeathEvent, EnemyType};
 use crate::resources::sprites::Sprites;
 use bevy::color::Alpha;
 use bevy::math::Quat;
-use bevy::prelude::{Commands, Component, DespawnRecursiveExt, Entity, EventReader, Query, Res, Sprite, Time, Transform, Vec3, With};
+use bevy::prelude::{Commands, Component, DespawnRecursiveExt, Entity, EventReader, Query, Res, ResMut, Sprite, Time, Transform, Vec3, With};
+use crate::rng::GameRng;
 use rand::Rng;
 
 #[derive(Component)]
@@ -13,8 +14,8 @@ pub fn create_effects_on_enemy_death(
     mut commands: Commands,
     sprites: Res<Sprites>,
     mut enemy_death_events: EventReader<EnemyDeathEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::rng();
     for event in enemy_death_events.read() {
         let explosion_sprite = match event.enemy_type {
             EnemyType::BlueFairy => sprites.effect_blue_explosion.clone(),
@@ -28,7 +29,7 @@ pub fn create_effects_on_enemy_death(
         ));
         // let x = rng.random_range(0.0..1.5);
         // let y = rng.random_range(0.0..1.5);
-        let rotation = rng.random_range(0.0..2.0 * PI);
+        let rotation = rng.cosmetic.random_range(0.0..2.0 * PI);
         commands.spawn((
             explosion_sprite,
             Transform::from_translation(event.position)
@@ -1,23 +1,27 @@
 use std::f32::consts::PI;
+use std::time::Duration;
 use crate::bullet_patterns::BulletPattern::SingleShotPattern;
 use crate::bullet_patterns::{BulletPattern, Target};
 use crate::enemy::EnemyType::*;
 use crate::game::{is_moving_laterally, GameObject, SpawnTimer, UP_DOWN_MOVEMENT_BRACKET};
 use crate::movement_patterns::MovementPatterns::StraightLinePattern;
 use crate::movement_patterns::{get_lateral_movement, run_movement_pattern, MovementPatterns};
-use crate::player::PlayerShot;
+use crate::player::{shot_hit_box, PlayerShot};
 use crate::resources::sprites::{use_side_indices, use_straight_indices, AnimatedSprite, AnimationIndices, Sprites};
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
 use crate::resources;
-use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume};
+use bevy::math::bounding::{BoundingCircle, IntersectsVolume};
 use bevy::prelude::*;
 use crate::bullet_patterns::single_shot::SingleShot;
 use crate::bullet_patterns::shot_schedule::ShotSchedule;
 use crate::movement_patterns::straight_line::StraightLine;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Enemy {
     pub enemy_type: EnemyType,
     pub hit_points: i32,
+    // how long it has been on screen, to tell how quickly it was shot down
+    pub age: Duration,
 }
 
 #[derive(Clone)]
@@ -27,7 +31,7 @@ pub enum EnemyType {
     Rumia,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct EnemySpawner {
     pub name: &'static str,
     pub enemy_type: EnemyType,
@@ -54,6 +58,7 @@ impl Default for EnemySpawner {
 pub struct EnemyDeathEvent {
     pub enemy_type: EnemyType,
     pub position: Vec3,
+    pub age: Duration,
 }
 
 #[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -67,6 +72,7 @@ pub fn spawn_enemy(commands: &mut Commands, sprites: &Res<Sprites>, spawner: &mu
         Enemy {
             enemy_type: enemy_spawner.enemy_type.clone(),
             hit_points: enemy_spawner.hit_points,
+            age: Duration::ZERO,
         },
         Transform::from_xyz(enemy_spawner.starting_position.x, enemy_spawner.starting_position.y, 0.6),
         animated_sprite.clone(),
@@ -81,9 +87,10 @@ pub fn spawn_enemy(commands: &mut Commands, sprites: &Res<Sprites>, spawner: &mu
 
 pub fn move_enemies(
     time: Res<Time>,
-    mut enemy_query: Query<(&mut Sprite, &mut MovementPatterns, &mut Transform, &mut AnimationIndices), With<Enemy>>,
+    mut enemy_query: Query<(&mut Enemy, &mut Sprite, &mut MovementPatterns, &mut Transform, &mut AnimationIndices)>,
 ) {
-    for (mut sprite, mut movement_pattern, mut transform, mut animation_indices) in enemy_query.iter_mut() {
+    for (mut enemy, mut sprite, mut movement_pattern, mut transform, mut animation_indices) in enemy_query.iter_mut() {
+        enemy.age += time.delta();
         run_movement_pattern(&mut movement_pattern, &mut transform, &time, false);
         let lateral_movement = get_lateral_movement(&*movement_pattern);
         if is_moving_laterally(movement_pattern, lateral_movement) {
@@ -110,25 +117,27 @@ pub fn spawn_enemies(
     }
 }
 
+// TODO: turn this into a box to account for different x and y
+pub fn enemy_hit_circle(enemy_sprite: &AnimatedSprite, enemy_transform: &Transform) -> BoundingCircle {
+    BoundingCircle::new(
+        enemy_transform.translation.truncate(),
+        enemy_sprite.sprite_size.x as f32 / 2.0
+    )
+}
+
 pub fn check_shot_enemy_collision(
     mut commands: Commands,
     mut enemy_query: Query<(&mut Enemy, &AnimatedSprite, &Transform)>,
     shot_query: Query<(&PlayerShot, &Transform, &Sprite, Entity)>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
 ) {
     for (mut enemy, enemy_sprite, enemy_transform) in enemy_query.iter_mut() {
-        // TODO: turn this into a box to account for different x and y
-        let enemy_hit_circle = BoundingCircle::new(
-            enemy_transform.translation.truncate(),
-            enemy_sprite.sprite_size.x as f32 / 2.0
-        );
+        let enemy_hit_circle = enemy_hit_circle(enemy_sprite, enemy_transform);
         for (shot, shot_transform, shot_sprite, shot_entity) in shot_query.iter() {
-            let shot_hit_box = Aabb2d::new(
-                shot_transform.translation.truncate(),
-                shot_sprite.rect.unwrap().half_size(),
-            );
-            if enemy_hit_circle.intersects(&shot_hit_box) {
+            if enemy_hit_circle.intersects(&shot_hit_box(shot_sprite, shot_transform)) {
                 enemy.hit_points -= shot.damage;
                 commands.entity(shot_entity).try_despawn();
+                play_sound_event_writer.send(PlaySoundEvent(SoundEffect::EnemyHit));
             }
         }
     }
@@ -144,6 +153,7 @@ pub fn check_for_enemy_death(
             enemy_death_events.send(EnemyDeathEvent {
                 enemy_type: enemy.enemy_type.clone(),
                 position: transform.translation,
+                age: enemy.age,
             });
             commands.entity(entity).despawn_recursive();
         }
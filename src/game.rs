@@ -1,99 +1,213 @@
 use crate::bullet::{fire_bullet_patterns, move_bullets, read_bullet_spawn_events, Bullet, BulletSpawnEvent};
-use crate::player::{check_bullet_player_collision, fire_shot, move_player, move_shot, respawn_invincibility, respawn_player, spawn_player, switch_player_sprite, PlayerContinueEvent, PlayerDeathEvent, PlayerShot, PlayerSystemSet};
-use crate::player_stats::{initialize_player_stats, listen_for_player_continue, listen_for_player_death};
+use crate::player::{Character, check_bullet_player_collision, check_graze, check_laser_player_collision, clear_bullets_during_bomb, fire_shot, move_player, move_shots, respawn_invincibility, respawn_player, spawn_player, switch_player_sprite, use_bomb, GrazeEvent, PlayerContinueEvent, PlayerDeathEvent, PlayerShot, PlayerSystemSet, ShotType};
+use crate::player_stats::{draw_bomb_counters, initialize_player_stats, listen_for_enemy_kills, listen_for_graze, listen_for_player_continue, listen_for_player_death};
 use crate::resources::sprites::{animate_sprite, Sprites};
 use crate::GameState;
 use bevy::prelude::*;
 use std::ops::Range;
+use std::time::Duration;
 use std::f32::consts::PI;
 use crate::bosses::boss::{spawn_bosses, update_bosses};
 use crate::bosses::boss_health_bar::BossDamageEvent;
 use crate::effects::{animate_enemy_death_explosions, create_effects_on_enemy_death};
 use crate::enemy::{check_for_enemy_death, check_shot_enemy_collision, move_enemies, spawn_enemies, Enemy, EnemyDeathEvent, EnemySystemSet};
+use crate::laser::{draw_lasers, read_laser_spawn_events, update_lasers, LaserSpawnEvent};
 use crate::level1::{level1_plugin, FirstLevelState};
 use crate::movement_patterns::{is_finished, MovementPatterns};
-use crate::resources::sounds::{listen_for_play_sound_events, PlaySoundEvent};
-use crate::testbed::testbed_plugin;
+use crate::resources::sounds::{listen_for_play_sound_events, play_gameplay_sounds, PlaySoundEvent};
+use crate::input::{read_player_input, Action, PlayerInput};
+use crate::replay::{replay_plugin, ReplayPlayback};
+use crate::rng::{reseed_game_rng, ChosenSeed, GameRng};
+use crate::simulation::{simulation_plugin, SimulationSet};
+use crate::snapshot::snapshot_plugin;
+use crate::time_control::time_control_plugin;
+use crate::practice::practice_plugin;
+use crate::high_scores::high_scores_plugin;
+use crate::rank::rank_plugin;
+use crate::stage_clear::stage_clear_plugin;
 
 pub const FRAME_BORDER_LEFT: f32 = -353.0;
 pub const FRAME_BORDER_TOP: f32 = 266.0;
 pub const FRAME_BORDER_RIGHT: f32 = 97.0;
 pub const FRAME_BORDER_BOTTOM: f32 = -258.0;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct GameObject;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct SpawnTimer(pub Timer);
 
+#[derive(Event)]
+pub struct StageClearEvent;
+
+// simulation time since the run started, so it ignores pauses and slowdown
+#[derive(Resource, Clone, Default)]
+pub struct StageClock(pub Duration);
+
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 pub enum LevelState {
     #[default]
     None,
-    TestBed,
     Level1,
 }
 
+impl LevelState {
+    // the stage that follows this one in a full run, if any
+    pub fn next(self) -> Option<LevelState> {
+        match self {
+            LevelState::None | LevelState::Level1 => None,
+        }
+    }
+}
+
+// where in the chosen level a run begins, so a section can be played without the ones before it
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum Checkpoint {
+    #[default]
+    Start,
+    Boss,
+    Spell(u8),
+    PostBoss,
+}
+
 #[derive(Resource)]
 pub struct ChosenLevel {
     pub level: LevelState,
+    pub checkpoint: Checkpoint,
 }
 
 impl Default for ChosenLevel {
     fn default() -> Self {
         Self {
             level: LevelState::None,
+            checkpoint: Checkpoint::Start,
         }
     }
 }
 
+#[derive(Resource, Clone, Copy, Default, Eq, PartialEq, Debug, Hash)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Lunatic,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Lunatic];
+
+    // chooses from one value per difficulty, listed easiest first
+    pub fn pick<T: Copy>(self, values: [T; 4]) -> T {
+        values[self as usize]
+    }
+
+    pub fn name(self) -> &'static str {
+        self.pick(["Easy", "Normal", "Hard", "Lunatic"])
+    }
+
+    pub fn key(self) -> &'static str {
+        self.pick(["easy", "normal", "hard", "lunatic"])
+    }
+
+    pub fn harder(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn easier(self) -> Self {
+        Self::ALL[(self as usize + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
 pub fn game_plugin(app: &mut App) {
     app
         .add_systems(OnEnter(GameState::StartingGame), (
+            reseed_game_rng,
+            reset_stage_clock,
             game_setup,
             initialize_player_stats,
-        ))
-        .add_systems(Update, listen_for_play_sound_events)
-        .add_systems(Update, (
+        ).chain())
+        .add_systems(Update, (play_gameplay_sounds, listen_for_play_sound_events).chain())
+        .add_systems(FixedUpdate, read_player_input
+            .run_if(not(resource_exists::<ReplayPlayback>))
+            .in_set(SimulationSet::Input))
+        .add_systems(FixedUpdate, (
             (
                 move_player,
+                fire_shot,
+                move_shots,
                 respawn_player,
                 respawn_invincibility,
-                switch_player_sprite,
-                check_bullet_player_collision,
-                listen_for_player_death,
-                listen_for_player_continue,
-                fire_shot,
-                move_shot,
-            ).in_set(PlayerSystemSet),
+            ).chain().in_set(PlayerSystemSet),
             (
                 spawn_enemies,
                 spawn_bosses,
-                read_bullet_spawn_events,
                 move_enemies,
-                fire_bullet_patterns,
                 update_bosses,
+                fire_bullet_patterns,
+                read_bullet_spawn_events,
+                read_laser_spawn_events,
+            ).chain().in_set(EnemySystemSet),
+            (
+                move_bullets,
+                update_lasers,
+            ).chain(),
+            (
                 check_shot_enemy_collision,
                 check_for_enemy_death,
-            ).in_set(EnemySystemSet),
-            animate_sprite,
-            move_bullets,
+            ).chain().in_set(EnemySystemSet),
+            (
+                use_bomb,
+                clear_bullets_during_bomb,
+                check_bullet_player_collision,
+                check_laser_player_collision,
+                check_graze,
+                listen_for_player_death,
+                listen_for_graze,
+                listen_for_enemy_kills,
+                listen_for_player_continue,
+            ).chain().in_set(PlayerSystemSet),
             out_of_bounds_cleanup,
+        ).chain().in_set(SimulationSet::Actors))
+        .add_systems(FixedUpdate, tick_stage_clock.in_set(SimulationSet::Stage))
+        .add_systems(Update, (
+            listen_for_pause,
+            switch_player_sprite,
+            draw_bomb_counters,
+            animate_sprite,
+            draw_lasers,
             create_effects_on_enemy_death,
             animate_enemy_death_explosions,
         ).run_if(in_state(GameState::PlayingGame)))
         .add_systems(OnEnter(LevelState::None), reset_levels)
         .add_plugins((
-            testbed_plugin,
+            simulation_plugin,
+            snapshot_plugin,
+            time_control_plugin,
+            replay_plugin,
+            practice_plugin,
+            high_scores_plugin,
+            rank_plugin,
+            stage_clear_plugin,
             level1_plugin,
         ))
         .init_state::<LevelState>()
+        .init_resource::<PlayerInput>()
+        .init_resource::<StageClock>()
+        .init_resource::<GameRng>()
+        .init_resource::<ChosenSeed>()
+        .init_resource::<Difficulty>()
+        .init_resource::<Character>()
+        .init_resource::<ShotType>()
         .add_event::<PlayerDeathEvent>()
         .add_event::<EnemyDeathEvent>()
         .add_event::<PlayerContinueEvent>()
         .add_event::<BulletSpawnEvent>()
+        .add_event::<LaserSpawnEvent>()
+        .add_event::<GrazeEvent>()
         .add_event::<BossDamageEvent>()
         .add_event::<PlaySoundEvent>()
+        .add_event::<StageClearEvent>()
     ;
 
 }
@@ -175,6 +289,26 @@ fn out_of_bounds_cleanup(
     }
 }
 
+fn reset_stage_clock(mut stage_clock: ResMut<StageClock>) {
+    stage_clock.0 = Duration::ZERO;
+}
+
+fn tick_stage_clock(
+    time: Res<Time>,
+    mut stage_clock: ResMut<StageClock>,
+) {
+    stage_clock.0 += time.delta();
+}
+
+fn listen_for_pause(
+    actions: Res<ButtonInput<Action>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        game_state.set(GameState::Paused);
+    }
+}
+
 fn reset_levels(
     mut first_level_state: ResMut<NextState<FirstLevelState>>,
 ) {
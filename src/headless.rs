@@ -0,0 +1,166 @@
+use std::time::Duration;
+use bevy::audio::AudioSource;
+use bevy::ecs::event::EventCursor;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use crate::game::{Checkpoint, ChosenLevel, Difficulty, LevelState};
+use crate::input::{GameButton, PlayerInput};
+use crate::music::{LoopingMusic, UnlockHeardMusic};
+use crate::player::{Character, PlayerDeathEvent, ShotType};
+use crate::replay::{start_replay_playback, Replay, ReplayHeader, ReplayPlayback};
+use crate::rank::RankEnabled;
+use crate::rng::ChosenSeed;
+use crate::simulation::SIMULATION_HZ;
+use crate::time_control::{RewindEvent, SimulationControl};
+use crate::{shmup_plugin, GameState};
+
+pub const MAX_STARTUP_FRAMES: usize = 10;
+pub const SECONDS: usize = SIMULATION_HZ as usize;
+
+// the whole game without a window, renderer or audio, advancing exactly one simulation tick per update
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default(), InputPlugin))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Font>()
+        .init_asset::<AudioSource>()
+        .init_asset::<LoopingMusic>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / SIMULATION_HZ)))
+        .add_plugins(shmup_plugin)
+        .insert_resource(UnlockHeardMusic(false))
+    ;
+    app
+}
+
+// drives a headless run by feeding scripted input through replay playback, one frame per step
+pub struct Harness {
+    pub app: App,
+    pub player_deaths: usize,
+    input: PlayerInput,
+    death_cursor: EventCursor<PlayerDeathEvent>,
+}
+
+impl Harness {
+    pub fn new(level: LevelState, seed: u64) -> Self {
+        Self::from_checkpoint(level, Checkpoint::Start, seed)
+    }
+
+    pub fn from_checkpoint(level: LevelState, checkpoint: Checkpoint, seed: u64) -> Self {
+        Self::from_header(ReplayHeader {
+            seed,
+            level,
+            checkpoint,
+            character: Character::Remilia,
+            shot_type: ShotType::Forward,
+            difficulty: Difficulty::Normal,
+            rank: RankEnabled::default(),
+            handoff: None,
+        })
+    }
+
+    pub fn from_header(header: ReplayHeader) -> Self {
+        let mut app = headless_app();
+        app.update();
+        let replay = Replay::new(header);
+        app.world_mut().run_system_once(move |
+            mut commands: Commands,
+            mut chosen_seed: ResMut<ChosenSeed>,
+            mut chosen_level: ResMut<ChosenLevel>,
+            mut game_state: ResMut<NextState<GameState>>,
+        | {
+            start_replay_playback(&mut commands, replay.clone(), &mut chosen_seed, &mut chosen_level);
+            game_state.set(GameState::StartingGame);
+        }).expect("could not start headless playback");
+
+        let mut harness = Self {
+            app,
+            player_deaths: 0,
+            input: PlayerInput::default(),
+            death_cursor: EventCursor::default(),
+        };
+        for _ in 0..MAX_STARTUP_FRAMES {
+            if harness.state::<GameState>() == GameState::PlayingGame {
+                break;
+            }
+            harness.step(1);
+        }
+        assert_eq!(harness.state::<GameState>(), GameState::PlayingGame, "headless run never started");
+        harness
+    }
+
+    pub fn hold(&mut self, input: PlayerInput) {
+        self.input = input;
+    }
+
+    pub fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            // queue exactly the next tick's input, since a tick may or may not run during this update
+            if let Some(mut playback) = self.app.world_mut().get_resource_mut::<ReplayPlayback>() {
+                let frame = playback.frame;
+                playback.replay.frames.truncate(frame);
+                playback.replay.frames.push(self.input);
+            }
+            self.app.update();
+            let deaths = self.app.world().resource::<Events<PlayerDeathEvent>>();
+            self.player_deaths += self.death_cursor.read(deaths).count();
+        }
+    }
+
+    pub fn simulation_control(&mut self) -> Mut<'_, SimulationControl> {
+        self.app.world_mut().resource_mut::<SimulationControl>()
+    }
+
+    // puts the run back to the nearest snapshot at least the given number of frames ago, then runs one frame on from it
+    pub fn rewind(&mut self, frames: usize) {
+        self.app.world_mut().send_event(RewindEvent { frames });
+        self.app.update();
+        // deaths before the rewind point were already counted the first time through
+        let deaths = self.app.world().resource::<Events<PlayerDeathEvent>>();
+        self.death_cursor.read(deaths).count();
+    }
+
+    pub fn frame(&self) -> usize {
+        self.app.world().resource::<ReplayPlayback>().frame
+    }
+
+    pub fn step_until<S: States>(&mut self, state: S, max_frames: usize) -> bool {
+        for _ in 0..max_frames {
+            if self.state::<S>() == state {
+                return true;
+            }
+            self.step(1);
+        }
+        self.state::<S>() == state
+    }
+
+    pub fn state<S: States>(&self) -> S {
+        self.app.world().resource::<State<S>>().get().clone()
+    }
+
+    pub fn count<C: Component>(&mut self) -> usize {
+        let world = self.app.world_mut();
+        world.query_filtered::<(), With<C>>().iter(world).count()
+    }
+
+    pub fn positions<C: Component>(&mut self) -> Vec<Vec3> {
+        let world = self.app.world_mut();
+        world.query_filtered::<&Transform, With<C>>().iter(world).map(|transform| transform.translation).collect()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+}
+
+pub fn holding(button: GameButton) -> PlayerInput {
+    let mut input = PlayerInput::default();
+    input.set(button, true);
+    input
+}
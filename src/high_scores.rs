@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use bevy::prelude::*;
+use crate::game::{Checkpoint, ChosenLevel, Difficulty};
+use crate::launch_options::LaunchOptions;
+use crate::player_stats::PlayerStats;
+use crate::practice::PracticeSession;
+use crate::replay::ReplayPlayback;
+use crate::settings::{data_path, save_data_file};
+use crate::GameState;
+
+pub const HIGH_SCORES_FILE_NAME: &str = "scores.txt";
+const MAX_HIGH_SCORES: usize = 10;
+
+// the best full-run scores, with a separate table for each difficulty
+#[derive(Default, Debug, PartialEq)]
+pub struct HighScores {
+    tables: HashMap<Difficulty, Vec<u64>>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        match fs::read_to_string(data_path(HIGH_SCORES_FILE_NAME)) {
+            Ok(text) => Self::decode(&text).unwrap_or_else(|error| {
+                error!("Could not read high scores: {}", error);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        save_data_file(HIGH_SCORES_FILE_NAME, self.encode())
+    }
+
+    pub fn encode(&self) -> String {
+        Difficulty::ALL.iter()
+            .flat_map(|difficulty| self.table(*difficulty).iter().map(move |score| format!("{} {}", difficulty.key(), score)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn decode(text: &str) -> io::Result<Self> {
+        let mut high_scores = Self::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [key, score] = fields.as_slice() else {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unexpected line {}", line)));
+            };
+            let difficulty = Difficulty::ALL.into_iter().find(|difficulty| difficulty.key() == *key)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown difficulty {}", key)))?;
+            let score = score.parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid score {}", score)))?;
+            high_scores.submit(difficulty, score);
+        }
+        Ok(high_scores)
+    }
+
+    pub fn table(&self, difficulty: Difficulty) -> &[u64] {
+        self.tables.get(&difficulty).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn best(&self, difficulty: Difficulty) -> u64 {
+        self.table(difficulty).first().copied().unwrap_or_default()
+    }
+
+    // returns whether the score made it onto the table
+    pub fn submit(&mut self, difficulty: Difficulty, score: u64) -> bool {
+        let table = self.tables.entry(difficulty).or_default();
+        let rank = table.partition_point(|entry| *entry >= score);
+        if rank >= MAX_HIGH_SCORES {
+            return false;
+        }
+        table.insert(rank, score);
+        table.truncate(MAX_HIGH_SCORES);
+        true
+    }
+}
+
+pub fn high_scores_plugin(app: &mut App) {
+    app
+        .add_systems(OnEnter(GameState::GameOver), submit_high_score)
+        .add_systems(OnEnter(GameState::Ending), submit_high_score)
+    ;
+}
+
+// only full runs played for real count, not practice, replays, retried sections, continued or invincible runs
+fn submit_high_score(
+    player_stats: Res<PlayerStats>,
+    difficulty: Res<Difficulty>,
+    chosen_level: Res<ChosenLevel>,
+    launch_options: Res<LaunchOptions>,
+    practice: Option<Res<PracticeSession>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if practice.is_some() || playback.is_some() || launch_options.invincible || player_stats.score == 0 {
+        return;
+    }
+    if chosen_level.checkpoint != Checkpoint::Start || player_stats.continues > 0 {
+        return;
+    }
+    let mut high_scores = HighScores::load();
+    if high_scores.submit(*difficulty, player_stats.score) {
+        if let Err(error) = high_scores.save() {
+            error!("Could not save high scores: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_difficulty_keeps_its_own_table() {
+        let mut high_scores = HighScores::default();
+        high_scores.submit(Difficulty::Normal, 500);
+        high_scores.submit(Difficulty::Normal, 900);
+        high_scores.submit(Difficulty::Lunatic, 300);
+
+        assert_eq!(high_scores.table(Difficulty::Normal), &[900, 500]);
+        assert_eq!(high_scores.best(Difficulty::Lunatic), 300);
+        assert_eq!(high_scores.best(Difficulty::Easy), 0);
+        assert_eq!(HighScores::decode(&high_scores.encode()).unwrap(), high_scores);
+    }
+
+    #[test]
+    fn only_the_best_scores_are_kept() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u64 {
+            high_scores.submit(Difficulty::Hard, score * 100);
+        }
+
+        assert!(!high_scores.submit(Difficulty::Hard, 50));
+        assert!(high_scores.submit(Difficulty::Hard, 150));
+        assert_eq!(high_scores.table(Difficulty::Hard).len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.table(Difficulty::Hard).last(), Some(&150));
+    }
+}
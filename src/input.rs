@@ -0,0 +1,378 @@
+use std::f32::consts::FRAC_PI_4;
+use std::fmt::Debug;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameButton {
+    Up,
+    Down,
+    Left,
+    Right,
+    Shot,
+    Focus,
+    Bomb,
+}
+
+impl GameButton {
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+// the buttons held during the current simulation tick, packed into one byte so replays stay small
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayerInput(pub u8);
+
+impl PlayerInput {
+    pub fn pressed(&self, button: GameButton) -> bool {
+        self.0 & button.bit() != 0
+    }
+
+    pub fn set(&mut self, button: GameButton, pressed: bool) {
+        if pressed {
+            self.0 |= button.bit();
+        } else {
+            self.0 &= !button.bit();
+        }
+    }
+}
+
+// what the game and menus read instead of raw keys, so every key can be rebound
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Shot,
+    Focus,
+    Bomb,
+    Pause,
+    Confirm,
+    Cancel,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Shot,
+        Action::Focus,
+        Action::Bomb,
+        Action::Pause,
+        Action::Confirm,
+        Action::Cancel,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Shot => "Shot",
+            Action::Focus => "Focus",
+            Action::Bomb => "Bomb",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::Cancel => "Cancel",
+        }
+    }
+
+    // how the action is written in the config file
+    pub fn key(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Shot => "shot",
+            Action::Focus => "focus",
+            Action::Bomb => "bomb",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+        }
+    }
+
+    fn is_menu_only(self) -> bool {
+        matches!(self, Action::Confirm | Action::Cancel)
+    }
+
+    // pause is read in both, since it also backs out of menus
+    fn is_gameplay_only(self) -> bool {
+        matches!(self, Action::Shot | Action::Focus | Action::Bomb)
+    }
+
+    // menu and gameplay actions are never read at the same time, so they may share a key
+    fn conflicts_with(self, other: Action) -> bool {
+        !(self.is_menu_only() && other.is_gameplay_only() || self.is_gameplay_only() && other.is_menu_only())
+    }
+}
+
+// the keys that can be bound, which is also how bindings are read back from the config file
+pub const BINDABLE_KEYS: [KeyCode; 51] = [
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF, KeyCode::KeyG,
+    KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN,
+    KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU,
+    KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight,
+    KeyCode::AltLeft, KeyCode::AltRight, KeyCode::Space, KeyCode::Enter, KeyCode::Escape,
+    KeyCode::Tab, KeyCode::Backspace,
+];
+
+// the gamepad buttons that can be bound, d-pad included. the left stick always moves as well
+pub const BINDABLE_BUTTONS: [GamepadButton; 16] = [
+    GamepadButton::South, GamepadButton::East, GamepadButton::North, GamepadButton::West,
+    GamepadButton::LeftTrigger, GamepadButton::LeftTrigger2, GamepadButton::RightTrigger, GamepadButton::RightTrigger2,
+    GamepadButton::Select, GamepadButton::Start, GamepadButton::LeftThumb, GamepadButton::RightThumb,
+    GamepadButton::DPadUp, GamepadButton::DPadDown, GamepadButton::DPadLeft, GamepadButton::DPadRight,
+];
+
+pub fn binding_name(binding: impl Debug) -> String {
+    format!("{:?}", binding)
+}
+
+pub fn parse_binding<T: Copy + Debug>(bindable: &[T], name: &str) -> Option<T> {
+    bindable.iter().copied().find(|binding| binding_name(binding) == name)
+}
+
+// a shorter name for the key config screen
+pub fn key_code_label(key: KeyCode) -> String {
+    let name = binding_name(key);
+    name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).unwrap_or(&name).to_string()
+}
+
+// one key or button for each action, indexed by the action
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings<T>([T; Action::ALL.len()]);
+
+pub type KeyBindings = Bindings<KeyCode>;
+pub type PadBindings = Bindings<GamepadButton>;
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self([
+            KeyCode::ArrowUp,
+            KeyCode::ArrowDown,
+            KeyCode::ArrowLeft,
+            KeyCode::ArrowRight,
+            KeyCode::KeyZ,
+            KeyCode::ShiftLeft,
+            KeyCode::KeyX,
+            KeyCode::Escape,
+            KeyCode::KeyZ,
+            KeyCode::KeyX,
+        ])
+    }
+}
+
+impl Default for PadBindings {
+    fn default() -> Self {
+        Self([
+            GamepadButton::DPadUp,
+            GamepadButton::DPadDown,
+            GamepadButton::DPadLeft,
+            GamepadButton::DPadRight,
+            GamepadButton::South,
+            GamepadButton::RightTrigger,
+            GamepadButton::East,
+            GamepadButton::Start,
+            GamepadButton::South,
+            GamepadButton::East,
+        ])
+    }
+}
+
+impl<T: Copy + PartialEq> Bindings<T> {
+    pub fn get(&self, action: Action) -> T {
+        self.0[action as usize]
+    }
+
+    // assigns the binding without touching other actions, for bindings read back from the config file
+    pub fn set(&mut self, action: Action, binding: T) {
+        self.0[action as usize] = binding;
+    }
+
+    // an action that already had the binding takes this action's old one, so nothing is left unbound.
+    // returns false and changes nothing if that would make it share a binding with an action it conflicts with
+    pub fn bind(&mut self, action: Action, binding: T) -> bool {
+        let old_binding = self.get(action);
+        let mut bindings = self.clone();
+        let mut swapped = Vec::new();
+        for other in Action::ALL {
+            if other != action && action.conflicts_with(other) && self.get(other) == binding {
+                bindings.set(other, old_binding);
+                swapped.push(other);
+            }
+        }
+        bindings.set(action, binding);
+        let conflict = swapped.into_iter().any(|swapped| Action::ALL.into_iter()
+            .any(|other| other != swapped && swapped.conflicts_with(other) && bindings.get(other) == old_binding));
+        if conflict {
+            return false;
+        }
+        *self = bindings;
+        true
+    }
+}
+
+// which directions the left stick is pushed in. with eight_way the stick snaps to the nearest
+// of eight directions, otherwise each axis counts on its own once it's past the deadzone
+pub fn stick_directions(stick: Vec2, deadzone: f32, eight_way: bool) -> [(Action, bool); 4] {
+    let (x, y) = if stick.length() < deadzone {
+        (0, 0)
+    } else if eight_way {
+        let sector = (stick.y.atan2(stick.x) / FRAC_PI_4).round() as i32;
+        let (x, y) = Vec2::from_angle(sector as f32 * FRAC_PI_4).into();
+        (x.round() as i32, y.round() as i32)
+    } else {
+        (
+            if stick.x.abs() < deadzone { 0 } else { stick.x.signum() as i32 },
+            if stick.y.abs() < deadzone { 0 } else { stick.y.signum() as i32 },
+        )
+    };
+    [
+        (Action::Up, y > 0),
+        (Action::Down, y < 0),
+        (Action::Left, x < 0),
+        (Action::Right, x > 0),
+    ]
+}
+
+pub fn input_plugin(app: &mut App) {
+    app
+        .init_resource::<ButtonInput<Action>>()
+        .add_systems(PreUpdate, (log_gamepad_connections, update_actions).after(InputSystem))
+    ;
+}
+
+// gamepads are read wherever they're plugged in, this just says so
+fn log_gamepad_connections(
+    mut gamepad_connection_event_reader: EventReader<GamepadConnectionEvent>,
+) {
+    for connection_event in gamepad_connection_event_reader.read() {
+        match &connection_event.connection {
+            GamepadConnection::Connected { name, .. } => info!("Gamepad connected: {}", name),
+            GamepadConnection::Disconnected => info!("Gamepad disconnected"),
+        }
+    }
+}
+
+fn update_actions(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_query: Query<&Gamepad>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ButtonInput<Action>>,
+) {
+    actions.clear();
+    let mut pressed: Vec<Action> = Action::ALL.into_iter()
+        .filter(|action| keyboard.pressed(settings.key_bindings.get(*action)))
+        .collect();
+    for gamepad in gamepad_query.iter() {
+        pressed.extend(Action::ALL.into_iter().filter(|action| gamepad.pressed(settings.pad_bindings.get(*action))));
+        pressed.extend(stick_directions(gamepad.left_stick(), settings.stick_deadzone(), settings.eight_way_stick)
+            .into_iter()
+            .filter(|(_, pushed)| *pushed)
+            .map(|(action, _)| action));
+    }
+    for action in Action::ALL {
+        if pressed.contains(&action) {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+pub fn read_player_input(
+    actions: Res<ButtonInput<Action>>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    let mut input = PlayerInput::default();
+    input.set(GameButton::Up, actions.pressed(Action::Up));
+    input.set(GameButton::Down, actions.pressed(Action::Down));
+    input.set(GameButton::Left, actions.pressed(Action::Left));
+    input.set(GameButton::Right, actions.pressed(Action::Right));
+    input.set(GameButton::Shot, actions.pressed(Action::Shot));
+    input.set(GameButton::Focus, actions.pressed(Action::Focus));
+    input.set(GameButton::Bomb, actions.pressed(Action::Bomb));
+    *player_input = input;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_taken_key_swaps_the_two_actions() {
+        let mut bindings = KeyBindings::default();
+        assert!(bindings.bind(Action::Shot, KeyCode::ShiftLeft));
+
+        assert_eq!(bindings.get(Action::Shot), KeyCode::ShiftLeft);
+        assert_eq!(bindings.get(Action::Focus), KeyCode::KeyZ);
+        // confirm is only read in menus, so it keeps sharing the old shot key
+        assert_eq!(bindings.get(Action::Confirm), KeyCode::KeyZ);
+    }
+
+    #[test]
+    fn a_swap_that_would_share_a_key_with_a_conflicting_action_is_rejected() {
+        let mut bindings = KeyBindings::default();
+
+        // up would take Z, which confirm still uses
+        assert!(!bindings.bind(Action::Shot, KeyCode::ArrowUp));
+        assert_eq!(bindings, KeyBindings::default());
+        assert!(bindings.bind(Action::Shot, KeyCode::KeyA));
+        assert_eq!(bindings.get(Action::Shot), KeyCode::KeyA);
+    }
+
+    #[test]
+    fn pause_cannot_share_a_key_with_a_menu_action() {
+        let mut bindings = KeyBindings::default();
+        assert!(bindings.bind(Action::Pause, KeyCode::KeyX));
+
+        assert_eq!(bindings.get(Action::Pause), KeyCode::KeyX);
+        assert_eq!(bindings.get(Action::Bomb), KeyCode::Escape);
+        assert_eq!(bindings.get(Action::Cancel), KeyCode::Escape);
+    }
+
+    #[test]
+    fn binding_names_round_trip() {
+        for key in BINDABLE_KEYS {
+            assert_eq!(parse_binding(&BINDABLE_KEYS, &binding_name(key)), Some(key));
+        }
+        for button in BINDABLE_BUTTONS {
+            assert_eq!(parse_binding(&BINDABLE_BUTTONS, &binding_name(button)), Some(button));
+        }
+        assert_eq!(key_code_label(KeyCode::KeyZ), "Z");
+    }
+
+    fn pushed(stick: Vec2, eight_way: bool) -> Vec<Action> {
+        stick_directions(stick, 0.25, eight_way).into_iter()
+            .filter(|(_, pushed)| *pushed)
+            .map(|(action, _)| action)
+            .collect()
+    }
+
+    #[test]
+    fn the_stick_only_moves_past_the_deadzone() {
+        assert_eq!(pushed(Vec2::new(0.2, -0.1), false), vec![]);
+        assert_eq!(pushed(Vec2::new(0.2, -0.6), false), vec![Action::Down]);
+        assert_eq!(pushed(Vec2::new(-0.5, 0.5), false), vec![Action::Up, Action::Left]);
+    }
+
+    #[test]
+    fn the_eight_way_stick_snaps_to_the_nearest_direction() {
+        assert_eq!(pushed(Vec2::new(0.9, 0.3), true), vec![Action::Right]);
+        assert_eq!(pushed(Vec2::new(0.6, 0.5), true), vec![Action::Up, Action::Right]);
+        assert_eq!(pushed(Vec2::new(-0.1, -0.8), true), vec![Action::Down]);
+    }
+}
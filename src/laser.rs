@@ -0,0 +1,364 @@
+use std::collections::VecDeque;
+use crate::game::{is_in_playfield, GameObject};
+use crate::movement_patterns::{run_movement_pattern, set_homing_target, MovementPatterns};
+use crate::movement_patterns::MovementPatterns::HomingPattern;
+use crate::player::Player;
+use bevy::prelude::*;
+
+const WARNING_LINE_WIDTH: f32 = 1.0;
+const HIT_WIDTH_RATIO: f32 = 0.5;
+const LASER_GRAZE_INTERVAL: f32 = 0.1;
+// no laser outlives this, even one whose head never leaves the playfield
+const MAX_LASER_LIFETIME: f32 = 10.0;
+
+#[derive(Component, Clone)]
+pub struct Laser {
+    pub laser_type: LaserType,
+    pub color: Color,
+    pub width: f32,
+    pub graze_timer: Timer,
+    pub lifetime: Timer,
+}
+
+#[derive(Component, Clone)]
+pub struct LaserSegment(usize);
+
+#[derive(Clone)]
+pub enum LaserType {
+    Fixed(FixedLaser),
+    Straight(StraightLaser),
+    Curvy(CurvyLaser),
+}
+
+// warns with a thin line, widens, holds for its duration and then disappears
+#[derive(Clone)]
+pub struct FixedLaser {
+    pub origin: Vec2,
+    pub angle: Rot2,
+    pub length: f32,
+    pub warning: Timer,
+    pub widen: Timer,
+    pub duration: Timer,
+}
+
+// a segment of fixed length travelling along its angle, growing out of its origin
+#[derive(Clone)]
+pub struct StraightLaser {
+    pub head: Vec2,
+    pub angle: Rot2,
+    pub speed: f32,
+    pub length: f32,
+    pub distance_travelled: f32,
+}
+
+// the head runs a movement pattern and the body trails behind along the head's path
+#[derive(Clone)]
+pub struct CurvyLaser {
+    pub head: Transform,
+    pub movement_pattern: MovementPatterns,
+    pub max_nodes: usize,
+    pub nodes: VecDeque<Vec2>,
+}
+
+#[derive(Event)]
+pub struct LaserSpawnEvent {
+    pub laser_type: LaserType,
+    pub color: Color,
+    pub width: f32,
+}
+
+pub fn create_fixed_laser(origin: Vec2, angle: Rot2, length: f32, warning_secs: f32, duration_secs: f32) -> LaserType {
+    LaserType::Fixed(FixedLaser {
+        origin,
+        angle,
+        length,
+        warning: Timer::from_seconds(warning_secs, TimerMode::Once),
+        widen: Timer::from_seconds(0.15, TimerMode::Once),
+        duration: Timer::from_seconds(duration_secs, TimerMode::Once),
+    })
+}
+
+pub fn create_straight_laser(origin: Vec2, angle: Rot2, speed: f32, length: f32) -> LaserType {
+    LaserType::Straight(StraightLaser {
+        head: origin,
+        angle,
+        speed,
+        length,
+        distance_travelled: 0.0,
+    })
+}
+
+pub fn create_curvy_laser(origin: Vec2, movement_pattern: MovementPatterns, max_nodes: usize) -> LaserType {
+    LaserType::Curvy(CurvyLaser {
+        head: Transform::from_xyz(origin.x, origin.y, 0.0),
+        movement_pattern,
+        max_nodes: max_nodes.max(2),
+        nodes: VecDeque::from([origin]),
+    })
+}
+
+impl LaserType {
+    pub fn aimed(&self, origin: Vec2, angle: Rot2) -> LaserType {
+        let mut laser_type = self.clone();
+        match &mut laser_type {
+            LaserType::Fixed(fixed) => {
+                fixed.origin = origin;
+                fixed.angle = angle;
+            }
+            LaserType::Straight(straight) => {
+                straight.head = origin;
+                straight.angle = angle;
+            }
+            LaserType::Curvy(curvy) => {
+                curvy.head.translation = origin.extend(0.0);
+                curvy.nodes = VecDeque::from([origin]);
+                if let HomingPattern(homing) = &mut curvy.movement_pattern {
+                    homing.angle = angle;
+                }
+            }
+        }
+        laser_type
+    }
+
+    fn segment_count(&self) -> usize {
+        match self {
+            LaserType::Fixed(_) | LaserType::Straight(_) => 1,
+            LaserType::Curvy(curvy) => curvy.max_nodes - 1,
+        }
+    }
+}
+
+impl Laser {
+    pub fn segments(&self) -> Vec<(Vec2, Vec2)> {
+        match &self.laser_type {
+            LaserType::Fixed(fixed) => {
+                vec![(fixed.origin, fixed.origin + direction(fixed.angle) * fixed.length)]
+            }
+            LaserType::Straight(straight) => {
+                let tail_distance = straight.distance_travelled.min(straight.length);
+                vec![(straight.head - direction(straight.angle) * tail_distance, straight.head)]
+            }
+            LaserType::Curvy(curvy) => {
+                curvy.nodes.iter()
+                    .zip(curvy.nodes.iter().skip(1))
+                    .map(|(start, end)| (*start, *end))
+                    .collect()
+            }
+        }
+    }
+
+    pub fn hit_width(&self) -> f32 {
+        match &self.laser_type {
+            LaserType::Fixed(fixed) if !fixed.widen.finished() => 0.0,
+            _ => self.width * HIT_WIDTH_RATIO,
+        }
+    }
+
+    fn visual_width(&self) -> f32 {
+        match &self.laser_type {
+            LaserType::Fixed(fixed) if !fixed.warning.finished() => WARNING_LINE_WIDTH,
+            LaserType::Fixed(fixed) => {
+                WARNING_LINE_WIDTH + (self.width - WARNING_LINE_WIDTH) * fixed.widen.fraction()
+            }
+            _ => self.width,
+        }
+    }
+
+    pub fn distance_to(&self, point: Vec2) -> f32 {
+        self.segments().iter()
+            .map(|(start, end)| distance_to_segment(point, *start, *end))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    fn is_finished(&self) -> bool {
+        if self.lifetime.finished() {
+            return true;
+        }
+        match &self.laser_type {
+            LaserType::Fixed(fixed) => fixed.duration.finished(),
+            LaserType::Straight(straight) => {
+                let (tail, head) = self.segments()[0];
+                straight.distance_travelled > straight.length && !is_in_playfield(tail) && !is_in_playfield(head)
+            }
+            LaserType::Curvy(curvy) => {
+                curvy.nodes.len() == curvy.max_nodes && curvy.nodes.iter().all(|node| !is_in_playfield(*node))
+            }
+        }
+    }
+}
+
+pub fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
+}
+
+pub fn read_laser_spawn_events(
+    mut commands: Commands,
+    mut laser_spawn_events: EventReader<LaserSpawnEvent>,
+) {
+    for event in laser_spawn_events.read() {
+        let laser = Laser {
+            laser_type: event.laser_type.clone(),
+            color: event.color,
+            width: event.width,
+            graze_timer: Timer::from_seconds(LASER_GRAZE_INTERVAL, TimerMode::Once),
+            lifetime: Timer::from_seconds(MAX_LASER_LIFETIME, TimerMode::Once),
+        };
+        let segment_count = laser.laser_type.segment_count();
+        commands.spawn((
+            Name::new("Laser"),
+            Transform::default(),
+            Visibility::default(),
+            laser,
+            GameObject,
+        )).with_children(|parent| {
+            for i in 0..segment_count {
+                parent.spawn((
+                    Name::new("LaserSegment"),
+                    Sprite::from_color(event.color, Vec2::ONE),
+                    Transform::from_xyz(0.0, 0.0, 0.7),
+                    Visibility::Hidden,
+                    LaserSegment(i),
+                ));
+            }
+        });
+    }
+}
+
+pub fn update_lasers(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut laser_query: Query<(&mut Laser, Entity)>,
+) {
+    let player_position = player_query.iter().next().map(|transform| transform.translation.truncate());
+    for (mut laser, entity) in laser_query.iter_mut() {
+        laser.graze_timer.tick(time.delta());
+        laser.lifetime.tick(time.delta());
+        match &mut laser.laser_type {
+            LaserType::Fixed(fixed) => {
+                if fixed.warning.tick(time.delta()).finished() && fixed.widen.tick(time.delta()).finished() {
+                    fixed.duration.tick(time.delta());
+                }
+            }
+            LaserType::Straight(straight) => {
+                let distance = straight.speed * time.delta_secs();
+                straight.head += direction(straight.angle) * distance;
+                straight.distance_travelled += distance;
+            }
+            LaserType::Curvy(curvy) => {
+                set_homing_target(&mut curvy.movement_pattern, player_position);
+                run_movement_pattern(&mut curvy.movement_pattern, &mut curvy.head, &time, false);
+                curvy.nodes.push_front(curvy.head.translation.truncate());
+                curvy.nodes.truncate(curvy.max_nodes);
+            }
+        }
+        if laser.is_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn draw_lasers(
+    laser_query: Query<(&Laser, &Children)>,
+    mut segment_query: Query<(&LaserSegment, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    for (laser, children) in laser_query.iter() {
+        let segments = laser.segments();
+        let width = laser.visual_width();
+        let alpha = if laser.hit_width() > 0.0 { 1.0 } else { 0.5 };
+        for child in children.iter() {
+            let Ok((segment, mut transform, mut sprite, mut visibility)) = segment_query.get_mut(*child) else {
+                continue;
+            };
+            match segments.get(segment.0) {
+                Some((start, end)) => {
+                    let difference = *end - *start;
+                    transform.translation = start.midpoint(*end).extend(transform.translation.z);
+                    transform.rotation = Quat::from_rotation_z(difference.y.atan2(difference.x));
+                    sprite.custom_size = Some(Vec2::new(difference.length(), width));
+                    sprite.color = laser.color.with_alpha(alpha);
+                    *visibility = Visibility::Inherited;
+                }
+                None => {
+                    *visibility = Visibility::Hidden;
+                }
+            }
+        }
+    }
+}
+
+fn direction(angle: Rot2) -> Vec2 {
+    Vec2::new(angle.cos, angle.sin)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use bevy::ecs::system::RunSystemOnce;
+    use super::*;
+
+    fn spawn_laser(world: &mut World, laser_type: LaserType) -> Entity {
+        world.spawn(Laser {
+            laser_type,
+            color: Color::WHITE,
+            width: 10.0,
+            graze_timer: Timer::from_seconds(LASER_GRAZE_INTERVAL, TimerMode::Once),
+            lifetime: Timer::from_seconds(MAX_LASER_LIFETIME, TimerMode::Once),
+        }).id()
+    }
+
+    fn world_ticking_every(millis: u64) -> World {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(millis));
+        world.insert_resource(time);
+        world
+    }
+
+    #[test]
+    fn distance_to_segment_measures_to_the_nearest_point_on_the_segment() {
+        let start = Vec2::new(0.0, 0.0);
+        let end = Vec2::new(10.0, 0.0);
+
+        assert_eq!(distance_to_segment(Vec2::new(5.0, 3.0), start, end), 3.0);
+        assert_eq!(distance_to_segment(Vec2::new(5.0, -4.0), start, end), 4.0);
+        assert_eq!(distance_to_segment(Vec2::new(-3.0, 4.0), start, end), 5.0);
+        assert_eq!(distance_to_segment(Vec2::new(13.0, 4.0), start, end), 5.0);
+        assert_eq!(distance_to_segment(Vec2::new(3.0, 4.0), start, start), 5.0);
+    }
+
+    #[test]
+    fn a_fixed_laser_only_hits_once_it_has_warned_and_widened() {
+        let mut world = world_ticking_every(100);
+        let laser = spawn_laser(&mut world, create_fixed_laser(Vec2::ZERO, Rot2::IDENTITY, 100.0, 0.3, 1.0));
+
+        let mut hit_widths = vec![];
+        for _ in 0..6 {
+            world.run_system_once(update_lasers).unwrap();
+            hit_widths.push(world.get::<Laser>(laser).unwrap().hit_width());
+        }
+
+        // three ticks of warning, then the widening finishes on the second tick after
+        assert_eq!(hit_widths, vec![0.0, 0.0, 0.0, 0.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    fn a_straight_laser_grows_out_of_its_origin_up_to_its_length() {
+        let mut world = world_ticking_every(100);
+        let laser = spawn_laser(&mut world, create_straight_laser(Vec2::ZERO, Rot2::IDENTITY, 200.0, 50.0));
+
+        world.run_system_once(update_lasers).unwrap();
+        assert_eq!(world.get::<Laser>(laser).unwrap().segments(), vec![(Vec2::ZERO, Vec2::new(20.0, 0.0))]);
+
+        for _ in 0..4 {
+            world.run_system_once(update_lasers).unwrap();
+        }
+        assert_eq!(world.get::<Laser>(laser).unwrap().segments(), vec![(Vec2::new(50.0, 0.0), Vec2::new(100.0, 0.0))]);
+    }
+}
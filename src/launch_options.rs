@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use crate::bosses::rumia::SPELL_COUNT;
+use crate::game::{Checkpoint, ChosenLevel, Difficulty, LevelState};
+use crate::player::{Character, ShotType};
+use crate::rank::RankEnabled;
+use crate::rng::ChosenSeed;
+use crate::GameState;
+
+pub const USAGE: &str = "\
+Usage: shmup [options]
+
+  --level <level1>                         start a level, skipping the menus
+  --checkpoint <start|boss|post-boss>      where in the level to start
+  --boss <rumia>                           start at a boss fight
+  --spell <number>                         start at one of the boss's spell cards
+  --difficulty <easy|normal|hard|lunatic>
+  --rank                                   patterns get harder the better the run goes
+  --character <remilia>
+  --shot <forward|homing>                  homing adds a fan of shots that chase enemies
+  --seed <number>                          seed the run's random number generator
+  --invincible                             the player can't be hit
+  --scale <1-4>                            window scale, overriding the saved setting
+  --help";
+
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct LaunchOptions {
+    pub level: Option<LevelState>,
+    pub checkpoint: Checkpoint,
+    pub difficulty: Difficulty,
+    pub rank: bool,
+    pub character: Character,
+    pub shot_type: ShotType,
+    pub seed: Option<u64>,
+    pub invincible: bool,
+    pub window_scale: Option<f32>,
+    pub show_help: bool,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            level: None,
+            checkpoint: Checkpoint::Start,
+            difficulty: Difficulty::Normal,
+            rank: false,
+            character: Character::Remilia,
+            shot_type: ShotType::Forward,
+            seed: None,
+            invincible: false,
+            window_scale: None,
+            show_help: false,
+        }
+    }
+}
+
+impl LaunchOptions {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        // where to start is worked out once everything is read, so the order of the options doesn't matter
+        let mut checkpoint = None;
+        let mut boss_level = None;
+        let mut spell = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--level" => options.level = Some(parse_level(&value()?)?),
+                "--checkpoint" => checkpoint = Some(parse_checkpoint(&value()?)?),
+                "--boss" => {
+                    boss_level = match value()?.as_str() {
+                        "rumia" => Some(LevelState::Level1),
+                        boss => return Err(format!("unknown boss {}", boss)),
+                    };
+                },
+                "--spell" => {
+                    let number = value()?;
+                    match number.parse::<u8>() {
+                        Ok(number) if (1..=SPELL_COUNT).contains(&number) => spell = Some(number),
+                        _ => return Err(format!("invalid spell {}, expected 1 to {}", number, SPELL_COUNT)),
+                    }
+                },
+                "--difficulty" => options.difficulty = parse_difficulty(&value()?)?,
+                "--rank" => options.rank = true,
+                "--character" => {
+                    options.character = match value()?.as_str() {
+                        "remilia" => Character::Remilia,
+                        character => return Err(format!("unknown character {}", character)),
+                    }
+                },
+                "--shot" => {
+                    options.shot_type = match value()?.as_str() {
+                        "forward" => ShotType::Forward,
+                        "homing" => ShotType::Homing,
+                        shot_type => return Err(format!("unknown shot type {}", shot_type)),
+                    }
+                },
+                "--seed" => {
+                    let seed = value()?;
+                    options.seed = Some(seed.parse().map_err(|_| format!("invalid seed {}", seed))?);
+                },
+                "--invincible" => options.invincible = true,
+                "--scale" => {
+                    let scale = value()?;
+                    match scale.parse::<f32>() {
+                        Ok(scale) if (1.0..=4.0).contains(&scale) => options.window_scale = Some(scale),
+                        _ => return Err(format!("invalid scale {}, expected 1 to 4", scale)),
+                    }
+                },
+                "--help" | "-h" => options.show_help = true,
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+
+        options.checkpoint = match (checkpoint, boss_level, spell) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+                return Err("--checkpoint can't be combined with --boss or --spell".to_string());
+            },
+            (Some(checkpoint), None, None) => checkpoint,
+            // a spell belongs to the boss, so naming both is fine
+            (None, _, Some(number)) => Checkpoint::Spell(number),
+            (None, Some(_), None) => Checkpoint::Boss,
+            (None, None, None) => Checkpoint::Start,
+        };
+        match (options.level, boss_level) {
+            (Some(level), Some(boss_level)) if level != boss_level => {
+                return Err("--boss isn't in the level given by --level".to_string());
+            },
+            (None, Some(boss_level)) => options.level = Some(boss_level),
+            _ => {},
+        }
+
+        // jumping to a section of a level implies the level
+        if options.checkpoint != Checkpoint::Start && options.level.is_none() {
+            options.level = Some(LevelState::Level1);
+        }
+        Ok(options)
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelState, String> {
+    match level {
+        "level1" => Ok(LevelState::Level1),
+        _ => Err(format!("unknown level {}", level)),
+    }
+}
+
+fn parse_checkpoint(checkpoint: &str) -> Result<Checkpoint, String> {
+    match checkpoint {
+        "start" => Ok(Checkpoint::Start),
+        "boss" => Ok(Checkpoint::Boss),
+        "post-boss" => Ok(Checkpoint::PostBoss),
+        _ => Err(format!("unknown checkpoint {}", checkpoint)),
+    }
+}
+
+fn parse_difficulty(difficulty: &str) -> Result<Difficulty, String> {
+    Difficulty::ALL.into_iter()
+        .find(|candidate| candidate.key() == difficulty)
+        .ok_or_else(|| format!("unknown difficulty {}", difficulty))
+}
+
+pub fn apply_launch_options(
+    launch_options: Res<LaunchOptions>,
+    mut chosen_level: ResMut<ChosenLevel>,
+    mut chosen_seed: ResMut<ChosenSeed>,
+    mut difficulty: ResMut<Difficulty>,
+    mut rank_enabled: ResMut<RankEnabled>,
+    mut character: ResMut<Character>,
+    mut shot_type: ResMut<ShotType>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    chosen_seed.0 = launch_options.seed;
+    *difficulty = launch_options.difficulty;
+    rank_enabled.0 = launch_options.rank;
+    *character = launch_options.character;
+    *shot_type = launch_options.shot_type;
+    if let Some(level) = launch_options.level {
+        chosen_level.level = level;
+        chosen_level.checkpoint = launch_options.checkpoint;
+        game_state.set(GameState::StartingGame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn no_arguments_leave_the_menus_in_place() {
+        assert_eq!(parse(""), Ok(LaunchOptions::default()));
+    }
+
+    #[test]
+    fn a_spell_implies_its_level() {
+        let options = parse("--spell 2 --seed 7 --invincible --difficulty lunatic --shot homing").unwrap();
+
+        assert_eq!(options.level, Some(LevelState::Level1));
+        assert_eq!(options.checkpoint, Checkpoint::Spell(2));
+        assert_eq!(options.seed, Some(7));
+        assert!(options.invincible);
+        assert_eq!(options.difficulty, Difficulty::Lunatic);
+        assert_eq!(options.shot_type, ShotType::Homing);
+    }
+
+    #[test]
+    fn where_to_start_doesnt_depend_on_the_order_of_the_options() {
+        assert_eq!(parse("--spell 2 --boss rumia"), parse("--boss rumia --spell 2"));
+        assert_eq!(parse("--spell 2 --boss rumia").unwrap().checkpoint, Checkpoint::Spell(2));
+        assert_eq!(parse("--boss rumia --level level1"), parse("--level level1 --boss rumia"));
+        assert_eq!(parse("--boss rumia").unwrap().checkpoint, Checkpoint::Boss);
+    }
+
+    #[test]
+    fn conflicting_starting_points_are_rejected() {
+        assert!(parse("--boss rumia --checkpoint start").is_err());
+        assert!(parse("--checkpoint post-boss --boss rumia").is_err());
+        assert!(parse("--spell 1 --checkpoint boss").is_err());
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(parse("--level level9").is_err());
+        assert!(parse("--scale 8").is_err());
+        assert!(parse("--spell 0").is_err());
+        assert!(parse("--spell 3").is_err());
+        assert!(parse("--seed").is_err());
+        assert!(parse("--shot laser").is_err());
+        assert!(parse("--fly").is_err());
+    }
+}
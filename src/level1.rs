@@ -2,22 +2,31 @@ use crate::bosses::rumia::{rumia_plugin, RumiaState};
 use crate::bullet::BulletType::*;
 use crate::bullet_patterns::shot_schedule::{create_shot_schedule, ShotSchedule};
 use crate::bullet_patterns::starburst::Starburst;
-use crate::bullet_patterns::BulletPattern::{ShotgunPattern, StarburstPattern};
+use crate::bullet_patterns::BulletPattern::{LaserPattern, ShotgunPattern, SingleShotPattern, StarburstPattern};
 use crate::bullet_patterns::{Target, ENDLESS};
 use crate::enemy::EnemyType::*;
 use crate::enemy::{Enemy, EnemySpawner};
-use crate::game::{GameObject, LevelState, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT};
+use crate::game::{Checkpoint, ChosenLevel, Difficulty, GameObject, LevelState, StageClearEvent, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
 use crate::movement_patterns::straight_line::create_straight_line_pattern;
-use crate::spawns::{horizontal_line, SpawnTimeTracker, SPAWN_CENTER, SPAWN_OUTSIDE_LEFT, SPAWN_LEFTMOST, SPAWN_TOP};
+use crate::spawns::{horizontal_line, SpawnTimeTracker, SPAWN_CENTER, SPAWN_OUTSIDE_LEFT, SPAWN_OUTSIDE_RIGHT, SPAWN_LEFTMOST, SPAWN_TOP};
+use crate::simulation::SimulationSet;
+use crate::snapshot::snapshot_state;
 use crate::GameState;
 use bevy::prelude::*;
 use std::f32::consts::PI;
 use std::time::Duration;
+use crate::bullet_patterns::laser_shot::LaserShot;
 use crate::bullet_patterns::shotgun::Shotgun;
-use crate::bullet_patterns::single_shot::single_shot_at_player;
+use crate::bullet_patterns::single_shot::{single_shot_at_player, SingleShot};
 use crate::movement_patterns::curved_line::CurvedLine;
+use crate::laser::{create_curvy_laser, create_fixed_laser, create_straight_laser};
 use crate::movement_patterns::decelerate::create_decelerate_pattern;
-use crate::movement_patterns::MovementPatterns::CurvedLinePattern;
+use crate::movement_patterns::easing::Easing;
+use crate::movement_patterns::homing::create_homing_pattern;
+use crate::movement_patterns::path::{bezier_to, create_path_pattern, line_to};
+use crate::movement_patterns::MovementPatterns::{CurvedLinePattern, DontMovePattern};
+use crate::movement_patterns::sequence::{create_sequence_pattern, step, timed_step};
+use crate::movement_patterns::{DontMove, MovementPatterns};
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 pub enum FirstLevelState {
@@ -26,36 +35,71 @@ pub enum FirstLevelState {
     PreRumia,
     Rumia,
     PostRumia,
+    Cleared,
 }
 
 pub fn level1_plugin(app: &mut App) {
     app
         .add_systems(OnEnter(LevelState::Level1), pre_rumia_setup)
-        .add_systems(Update, listen_for_rumia_entrance
-            .run_if(in_state(FirstLevelState::PreRumia)))
+        .add_systems(FixedUpdate, listen_for_rumia_entrance
+            .run_if(in_state(FirstLevelState::PreRumia))
+            .in_set(SimulationSet::Stage))
         .add_systems(OnEnter(FirstLevelState::PostRumia), post_rumia_setup)
+        .add_systems(FixedUpdate, listen_for_stage_clear
+            .run_if(in_state(FirstLevelState::PostRumia))
+            .in_set(SimulationSet::Stage))
         .add_systems(OnEnter(FirstLevelState::Inactive), first_level_cleanup)
-        .add_plugins(rumia_plugin)
+        .add_plugins((rumia_plugin, snapshot_state::<FirstLevelState>))
         .init_state::<FirstLevelState>()
     ;
 }
 
+// the checkpoint a retry starts over from, which is the start of whichever section is being played
+pub fn current_checkpoint(level_state: FirstLevelState, rumia_state: RumiaState) -> Checkpoint {
+    match (level_state, rumia_state) {
+        (FirstLevelState::Rumia, RumiaState::Spell1) => Checkpoint::Spell(1),
+        (FirstLevelState::Rumia, RumiaState::Spell2) => Checkpoint::Spell(2),
+        (FirstLevelState::Rumia, RumiaState::Complete) => Checkpoint::PostBoss,
+        (FirstLevelState::Rumia, _) => Checkpoint::Boss,
+        (FirstLevelState::PostRumia | FirstLevelState::Cleared, _) => Checkpoint::PostBoss,
+        _ => Checkpoint::Start,
+    }
+}
+
 fn pre_rumia_setup(
     mut commands: Commands,
     mut next_state: ResMut<NextState<FirstLevelState>>,
+    chosen_level: Res<ChosenLevel>,
+    difficulty: Res<Difficulty>,
 ) {
+    match chosen_level.checkpoint {
+        Checkpoint::Start => {},
+        Checkpoint::Boss | Checkpoint::Spell(_) => {
+            next_state.set(FirstLevelState::Rumia);
+            return;
+        },
+        Checkpoint::PostBoss => {
+            next_state.set(FirstLevelState::PostRumia);
+            return;
+        },
+    }
+
     let mut spawn_delay = SpawnTimeTracker::default();
 
-    // dual_curves(&mut commands, &mut spawn_delay);
-    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(SPAWN_CENTER, SPAWN_TOP));
-    //
-    // spawn_delay.increment(2.0);
-    //
-    // decelerate_lines(&mut commands, &mut spawn_delay);
+    // dual_curves(&mut commands, &mut spawn_delay, *difficulty);
+    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(SPAWN_CENTER, SPAWN_TOP), *difficulty);
+
+    spawn_delay.increment(2.0);
+
+    decelerate_lines(&mut commands, &mut spawn_delay, *difficulty);
+
+    spawn_delay.increment(2.0);
+
+    swooping_fairies(&mut commands, &mut spawn_delay, *difficulty);
     //
     // spawn_delay.increment(2.0);
     //
-    // starbursts_from_sides(&mut commands, spawn_delay);
+    // starbursts_from_sides(&mut commands, spawn_delay, *difficulty);
 
     next_state.set(FirstLevelState::PreRumia);
 }
@@ -63,32 +107,93 @@ fn pre_rumia_setup(
 fn post_rumia_setup(
     mut commands: Commands,
     mut next_state: ResMut<NextState<FirstLevelState>>,
+    difficulty: Res<Difficulty>,
 ) {
     let mut spawn_delay = SpawnTimeTracker::default();
-    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(FRAME_BORDER_LEFT, SPAWN_TOP));
-    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(SPAWN_CENTER, SPAWN_TOP));
-    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(FRAME_BORDER_RIGHT, SPAWN_TOP));
+    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(FRAME_BORDER_LEFT, SPAWN_TOP), *difficulty);
+    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(SPAWN_CENTER, SPAWN_TOP), *difficulty);
+    shotgun_big_fairy(&mut commands, &mut spawn_delay, Vec2::new(FRAME_BORDER_RIGHT, SPAWN_TOP), *difficulty);
+
+    spawn_delay.increment(3.0);
+
+    laser_fairies(&mut commands, &mut spawn_delay, *difficulty);
+
+    spawn_delay.increment(4.0);
+
+    straight_laser_fairies(&mut commands, &mut spawn_delay, *difficulty);
 
     next_state.set(FirstLevelState::PostRumia);
 }
 
-fn shotgun_big_fairy(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker, starting_position: Vec2) {
+fn shotgun_big_fairy(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker, starting_position: Vec2, difficulty: Difficulty) {
     commands.spawn((
         Name::new("EnemySpawner"),
         EnemySpawner {
             name: "Big Fairy",
             enemy_type: BigFairy,
-            hit_points: 25,
+            hit_points: difficulty.pick([15, 25, 35, 45]),
             starting_position,
             movement_pattern: create_straight_line_pattern(Rot2::degrees(270.0), 30.0),
             bullet_pattern: ShotgunPattern(
                 Shotgun {
-                    bullets: vec![RedRimmedCircle; 5],
+                    bullets: vec![RedRimmedCircle; difficulty.pick([3, 5, 7, 9])],
                     spread: PI / 12.0,
-                    speed_range: (150.0, 200.0),
+                    speed_range: difficulty.pick([(120.0, 160.0), (150.0, 200.0), (170.0, 230.0), (190.0, 260.0)]),
+                },
+                Target::Player,
+                create_shot_schedule(1.0, difficulty.pick([1.4, 1.0, 0.8, 0.6]), ENDLESS),
+            ),
+        },
+        spawn_delay.create_timer_and_increment(1.0),
+        GameObject,
+    ));
+}
+
+// two fairies at the sides warn and then fire lasers at the player while one in the middle sends out homing curvy lasers
+fn laser_fairies(commands: &mut Commands, spawn_delay: &mut SpawnTimeTracker, difficulty: Difficulty) {
+    for x in [FRAME_BORDER_LEFT + 80.0, FRAME_BORDER_RIGHT - 80.0] {
+        commands.spawn((
+            Name::new("EnemySpawner"),
+            EnemySpawner {
+                name: "Blue Fairy",
+                enemy_type: BlueFairy,
+                hit_points: difficulty.pick([10, 15, 20, 25]),
+                starting_position: Vec2::new(x, SPAWN_TOP),
+                movement_pattern: enter_hover_exit_pattern(),
+                bullet_pattern: LaserPattern(
+                    LaserShot {
+                        laser_type: create_fixed_laser(Vec2::ZERO, Rot2::IDENTITY, 900.0, difficulty.pick([1.2, 0.9, 0.7, 0.6]), 0.8),
+                        color: Color::srgb(0.4, 0.6, 1.0),
+                        width: 14.0,
+                        num_lines: 1,
+                        spread: 0.0,
+                    },
+                    Target::Player,
+                    create_shot_schedule(0.2, 2.0, 1).during_movement_step(HOVER_STEP),
+                ),
+            },
+            spawn_delay.create_timer_and_increment(0.0),
+            GameObject,
+        ));
+    }
+    commands.spawn((
+        Name::new("EnemySpawner"),
+        EnemySpawner {
+            name: "Big Fairy",
+            enemy_type: BigFairy,
+            hit_points: difficulty.pick([20, 30, 40, 50]),
+            starting_position: Vec2::new(SPAWN_CENTER, SPAWN_TOP),
+            movement_pattern: enter_hover_exit_pattern(),
+            bullet_pattern: LaserPattern(
+                LaserShot {
+                    laser_type: create_curvy_laser(Vec2::ZERO, create_homing_pattern(Rot2::degrees(270.0), 220.0, 1.2, Some(1.5)), 24),
+                    color: Color::srgb(1.0, 0.4, 0.4),
+                    width: 10.0,
+                    num_lines: difficulty.pick([2, 3, 4, 5]),
+                    spread: PI / 2.0,
                 },
                 Target::Player,
-                create_shot_schedule(1.0, 1.0, ENDLESS),
+                create_shot_schedule(0.5, 1.2, 1).during_movement_step(HOVER_STEP),
             ),
         },
         spawn_delay.create_timer_and_increment(1.0),
@@ -96,7 +201,36 @@ fn shotgun_big_fairy(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTra
     ));
 }
 
-fn starbursts_from_sides(commands: &mut Commands, mut spawn_delay: SpawnTimeTracker) {
+// a pair of fairies fan out travelling lasers at the player while they hover
+fn straight_laser_fairies(commands: &mut Commands, spawn_delay: &mut SpawnTimeTracker, difficulty: Difficulty) {
+    for x in [SPAWN_CENTER - 120.0, SPAWN_CENTER + 120.0] {
+        commands.spawn((
+            Name::new("EnemySpawner"),
+            EnemySpawner {
+                name: "Blue Fairy",
+                enemy_type: BlueFairy,
+                hit_points: difficulty.pick([10, 15, 20, 25]),
+                starting_position: Vec2::new(x, SPAWN_TOP),
+                movement_pattern: enter_hover_exit_pattern(),
+                bullet_pattern: LaserPattern(
+                    LaserShot {
+                        laser_type: create_straight_laser(Vec2::ZERO, Rot2::IDENTITY, difficulty.pick([180.0, 220.0, 250.0, 280.0]), 120.0),
+                        color: Color::srgb(0.5, 1.0, 0.5),
+                        width: 8.0,
+                        num_lines: difficulty.pick([3, 3, 5, 5]),
+                        spread: PI / 4.0,
+                    },
+                    Target::Player,
+                    create_shot_schedule(0.3, 1.0, difficulty.pick([2, 2, 3, 3])).during_movement_step(HOVER_STEP),
+                ),
+            },
+            spawn_delay.create_timer_and_increment(0.5),
+            GameObject,
+        ));
+    }
+}
+
+fn starbursts_from_sides(commands: &mut Commands, mut spawn_delay: SpawnTimeTracker, difficulty: Difficulty) {
     let mut spawn_delay_2 = spawn_delay.clone();
     for _ in 0..3 {
         commands.spawn((
@@ -109,13 +243,13 @@ fn starbursts_from_sides(commands: &mut Commands, mut spawn_delay: SpawnTimeTrac
                 bullet_pattern: StarburstPattern(
                     Starburst {
                         bullets: vec![BlueRimmedCircle],
-                        num_lines: 6,
-                        speed_range: (200.0, 400.0),
+                        num_lines: difficulty.pick([4, 6, 8, 10]),
+                        speed_range: difficulty.pick([(160.0, 320.0), (200.0, 400.0), (220.0, 440.0), (240.0, 480.0)]),
                         spread: PI,
                         ..default()
                     },
                     Target::Angle(Rot2::degrees(-90.0)),
-                    create_shot_schedule(0.5, 1.0, ENDLESS),
+                    create_shot_schedule(0.5, difficulty.pick([1.4, 1.0, 0.8, 0.6]), ENDLESS),
                 ),
                 ..default()
             },
@@ -135,16 +269,17 @@ fn starbursts_from_sides(commands: &mut Commands, mut spawn_delay: SpawnTimeTrac
                 bullet_pattern: StarburstPattern(
                     Starburst {
                         bullets: vec![BlueRimmedCircle],
-                        num_lines: 6,
-                        speed_range: (200.0, 400.0),
+                        num_lines: difficulty.pick([4, 6, 8, 10]),
+                        speed_range: difficulty.pick([(160.0, 320.0), (200.0, 400.0), (220.0, 440.0), (240.0, 480.0)]),
                         spread: PI,
                         ..default()
                     },
                     Target::Angle(Rot2::degrees(90.0)),
                     ShotSchedule {
                         delay: Timer::from_seconds(0.5, TimerMode::Once),
-                        interval: Timer::from_seconds(1.0, TimerMode::Once),
+                        interval: Timer::from_seconds(difficulty.pick([1.4, 1.0, 0.8, 0.6]), TimerMode::Once),
                         repetitions: ENDLESS,
+                        ..default()
                     }
                 ),
                 ..default()
@@ -155,7 +290,7 @@ fn starbursts_from_sides(commands: &mut Commands, mut spawn_delay: SpawnTimeTrac
     }
 }
 
-fn dual_curves(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker) {
+fn dual_curves(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker, difficulty: Difficulty) {
     for _ in 0..5 {
         let starting_position = Vec2::new(FRAME_BORDER_LEFT, SPAWN_TOP);
         commands.spawn((
@@ -174,7 +309,12 @@ fn dual_curves(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker)
                         starting_position,
                     },
                 ),
-                bullet_pattern: single_shot_at_player(WhiteArrow, 200.0, 0.5, ENDLESS),
+                bullet_pattern: single_shot_at_player(
+                    WhiteArrow,
+                    difficulty.pick([160.0, 200.0, 230.0, 260.0]),
+                    difficulty.pick([0.8, 0.5, 0.4, 0.3]),
+                    ENDLESS,
+                ),
                 ..default()
             },
             spawn_delay.create_timer_and_increment(0.4),
@@ -200,7 +340,12 @@ fn dual_curves(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker)
                         starting_position,
                     },
                 ),
-                bullet_pattern: single_shot_at_player(WhiteArrow, 200.0, 0.5, ENDLESS),
+                bullet_pattern: single_shot_at_player(
+                    WhiteArrow,
+                    difficulty.pick([160.0, 200.0, 230.0, 260.0]),
+                    difficulty.pick([0.8, 0.5, 0.4, 0.3]),
+                    ENDLESS,
+                ),
                 ..default()
             },
             spawn_delay.create_timer_and_increment(0.4),
@@ -209,7 +354,7 @@ fn dual_curves(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker)
     };
 }
 
-fn decelerate_lines(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTracker) {
+fn decelerate_lines(commands: &mut Commands, spawn_delay: &mut SpawnTimeTracker, difficulty: Difficulty) {
     for _ in 0..2 {
         let spawn_line_left = horizontal_line(FRAME_BORDER_LEFT, SPAWN_CENTER, SPAWN_TOP, 5);
         let spawn_line_right = horizontal_line(SPAWN_CENTER, FRAME_BORDER_RIGHT, SPAWN_TOP, 5);
@@ -220,8 +365,15 @@ fn decelerate_lines(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTrac
                     name: "Blue Fairy",
                     enemy_type: BlueFairy,
                     starting_position,
-                    movement_pattern: create_decelerate_pattern(Rot2::degrees(270.0), 400.0, 20.0, Duration::from_secs(2)),
-                    bullet_pattern: single_shot_at_player(BlueRimmedCircle, 200.0, 0.5, ENDLESS),
+                    movement_pattern: enter_hover_exit_pattern(),
+                    bullet_pattern: SingleShotPattern(
+                        SingleShot {
+                            bullet_type: BlueRimmedCircle,
+                            speed: difficulty.pick([160.0, 200.0, 230.0, 260.0]),
+                        },
+                        Target::Player,
+                        create_shot_schedule(0.0, difficulty.pick([0.8, 0.5, 0.4, 0.3]), ENDLESS).during_movement_step(HOVER_STEP),
+                    ),
                     ..default()
                 },
                 spawn_delay.create_timer_and_increment(0.2),
@@ -238,8 +390,15 @@ fn decelerate_lines(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTrac
                     name: "Blue Fairy",
                     enemy_type: BlueFairy,
                     starting_position,
-                    movement_pattern: create_decelerate_pattern(Rot2::degrees(270.0), 400.0, 20.0, Duration::from_secs(2)),
-                    bullet_pattern: single_shot_at_player(BlueRimmedCircle, 200.0, 0.5, ENDLESS),
+                    movement_pattern: enter_hover_exit_pattern(),
+                    bullet_pattern: SingleShotPattern(
+                        SingleShot {
+                            bullet_type: BlueRimmedCircle,
+                            speed: difficulty.pick([160.0, 200.0, 230.0, 260.0]),
+                        },
+                        Target::Player,
+                        create_shot_schedule(0.0, difficulty.pick([0.8, 0.5, 0.4, 0.3]), ENDLESS).during_movement_step(HOVER_STEP),
+                    ),
                     ..default()
                 },
                 spawn_delay.create_timer_and_increment(0.2),
@@ -249,6 +408,53 @@ fn decelerate_lines(commands: &mut Commands, mut spawn_delay: &mut SpawnTimeTrac
     }
 }
 
+// fairies swoop in from one side, stop to fire a few shots and then leave over the opposite side
+fn swooping_fairies(commands: &mut Commands, spawn_delay: &mut SpawnTimeTracker, difficulty: Difficulty) {
+    for (from_x, direction) in [(SPAWN_OUTSIDE_LEFT, 1.0), (SPAWN_OUTSIDE_RIGHT, -1.0)] {
+        for i in 0..difficulty.pick([3, 4, 5, 6]) {
+            let stop = Vec2::new(SPAWN_CENTER - direction * (150.0 - 50.0 * i as f32), 100.0 + 20.0 * i as f32);
+            commands.spawn((
+                Name::new("EnemySpawner"),
+                EnemySpawner {
+                    name: "Blue Fairy",
+                    enemy_type: BlueFairy,
+                    starting_position: Vec2::new(from_x, 220.0),
+                    movement_pattern: create_path_pattern(vec![
+                        bezier_to(Vec2::new(from_x + direction * 150.0, 220.0), Vec2::new(stop.x - direction * 100.0, stop.y - 80.0), stop, 260.0)
+                            .with_easing(Easing::Back)
+                            .with_pause(1.5),
+                        line_to(Vec2::new(SPAWN_CENTER + direction * 400.0, FRAME_BORDER_TOP + 150.0), 180.0)
+                            .with_easing(Easing::EaseIn),
+                    ]),
+                    bullet_pattern: SingleShotPattern(
+                        SingleShot {
+                            bullet_type: BlueRimmedCircle,
+                            speed: difficulty.pick([160.0, 200.0, 230.0, 260.0]),
+                        },
+                        Target::Player,
+                        create_shot_schedule(1.5, 0.5, difficulty.pick([1, 2, 3, 3])),
+                    ),
+                    ..default()
+                },
+                spawn_delay.create_timer_and_increment(0.4),
+                GameObject,
+            ));
+        }
+
+        spawn_delay.increment(1.0);
+    }
+}
+
+const HOVER_STEP: usize = 1;
+
+fn enter_hover_exit_pattern() -> MovementPatterns {
+    create_sequence_pattern(vec![
+        step(create_decelerate_pattern(Rot2::degrees(270.0), 400.0, 20.0, Duration::from_secs(2))),
+        timed_step(DontMovePattern(DontMove), 3.0),
+        step(create_straight_line_pattern(Rot2::degrees(90.0), 150.0)),
+    ], false)
+}
+
 fn listen_for_rumia_entrance(
     spawns: Query<&EnemySpawner>,
     enemies: Query<&Enemy>,
@@ -265,9 +471,60 @@ fn listen_for_rumia_entrance(
     }
 }
 
+fn listen_for_stage_clear(
+    spawns: Query<&EnemySpawner>,
+    enemies: Query<&Enemy>,
+    mut next_first_level_state: ResMut<NextState<FirstLevelState>>,
+    mut stage_clear_event_writer: EventWriter<StageClearEvent>,
+) {
+    if spawns.is_empty() && enemies.is_empty() {
+        next_first_level_state.set(FirstLevelState::Cleared);
+        stage_clear_event_writer.send(StageClearEvent);
+    }
+}
 
 fn first_level_cleanup(
     mut state: ResMut<NextState<RumiaState>>,
 ) {
     state.set(RumiaState::Inactive);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bosses::boss::Boss;
+    use crate::bullet::Bullet;
+    use crate::headless::{holding, Harness, SECONDS};
+    use crate::input::GameButton;
+    use crate::launch_options::LaunchOptions;
+
+    #[test]
+    fn the_opening_fairy_fires_at_the_player() {
+        let mut harness = Harness::new(LevelState::Level1, 1);
+
+        harness.step(3 * SECONDS);
+
+        assert_eq!(harness.state::<FirstLevelState>(), FirstLevelState::PreRumia);
+        assert_eq!(harness.count::<Enemy>(), 1);
+        assert!(harness.count::<Bullet>() > 0);
+    }
+
+    #[test]
+    fn getting_through_the_opening_waves_brings_out_rumia() {
+        let mut harness = Harness::new(LevelState::Level1, 1);
+        harness.world_mut().resource_mut::<LaunchOptions>().invincible = true;
+        harness.hold(holding(GameButton::Shot));
+
+        assert!(harness.step_until(FirstLevelState::Rumia, 30 * SECONDS));
+        harness.step(1);
+        assert_eq!(harness.count::<Boss>(), 1);
+    }
+
+    #[test]
+    fn a_spell_checkpoint_skips_straight_to_that_spell() {
+        let mut harness = Harness::from_checkpoint(LevelState::Level1, Checkpoint::Spell(2), 1);
+
+        assert_eq!(harness.count::<Enemy>(), 0);
+        assert!(harness.step_until(RumiaState::Spell2, 5 * SECONDS));
+    }
+}
@@ -4,6 +4,7 @@ mod game;
 mod player;
 mod level1;
 mod bullet;
+mod laser;
 mod enemy;
 mod movement_patterns;
 mod bullet_patterns;
@@ -11,21 +12,49 @@ mod player_stats;
 mod menus;
 mod resources;
 mod bosses;
-mod testbed;
 mod spawns;
 mod effects;
+mod simulation;
+mod rng;
+mod input;
+mod replay;
+mod snapshot;
+mod practice;
+mod high_scores;
+mod stage_clear;
+mod rank;
+mod time_control;
+mod settings;
+mod music;
+mod launch_options;
+mod debug_overlay;
+#[cfg(test)]
+mod headless;
 
 use crate::game::{game_plugin, ChosenLevel, GameObject, LevelState};
 use crate::menus::game_over_menu::game_over_menu_plugin;
 use crate::menus::main_menu::main_menu_plugin;
 use crate::player_stats::PlayerStats;
 use bevy::prelude::*;
-use bevy::window::WindowResolution;
+#[cfg(debug_assertions)]
+use bevy::input::common_conditions::input_toggle_active;
+#[cfg(debug_assertions)]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use crate::debug_overlay::debug_overlay_plugin;
 use resources::images::{load_images, Images};
 use resources::sprites::{load_sprites, Sprites};
 use crate::menus::pause_menu::pause_menu_plugin;
+use crate::menus::replay_menu::replay_menu_plugin;
+use crate::menus::practice_menu::practice_menu_plugin;
+use crate::menus::options_menu::options_menu_plugin;
+use crate::menus::key_config_menu::key_config_menu_plugin;
+use crate::menus::music_room_menu::music_room_menu_plugin;
+use crate::launch_options::{apply_launch_options, LaunchOptions, USAGE};
 use crate::resources::sounds::{load_sounds, Sounds};
+use crate::settings::{settings_plugin, Settings};
+use crate::input::input_plugin;
+use crate::music::{music_plugin, LoopingMusic};
+use bevy::audio::AddAudioSource;
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 enum GameState {
@@ -36,27 +65,59 @@ enum GameState {
     GameOver,
     Paused,
     Resetting,
+    ReplayMenu,
+    PracticeMenu,
+    OptionsMenu,
+    KeyConfigMenu,
+    MusicRoom,
+    StageClear,
+    Ending,
 }
 
 const DEFAULT_RESOLUTION: Vec2 = Vec2::new(800., 600.);
-const SCALING_FACTOR: f32 = 2.0;
 
 fn main() {
-    App::new()
+    let launch_options = match LaunchOptions::parse(std::env::args().skip(1)) {
+        Ok(launch_options) => launch_options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if launch_options.show_help {
+        println!("{}", USAGE);
+        return;
+    }
+    let settings = Settings::load();
+    let scaling_factor = launch_options.window_scale.unwrap_or(settings.window_scale as f32);
+
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins
             .set(ImagePlugin::default_nearest())
             .set(WindowPlugin {
                 primary_window: Some(Window {
-                    resolution: WindowResolution::new(
-                            DEFAULT_RESOLUTION.x * SCALING_FACTOR,
-                            DEFAULT_RESOLUTION.y * SCALING_FACTOR
-                        )
-                        .with_scale_factor_override(SCALING_FACTOR),
+                    resolution: settings.window_resolution(scaling_factor),
+                    mode: settings.window_mode(),
+                    present_mode: settings.present_mode(),
                     ..default()
                 }),
                 ..default()
             })
         )
+        .insert_resource(launch_options)
+        .insert_resource(settings)
+        .add_audio_source::<LoopingMusic>()
+        .add_plugins((shmup_plugin, debug_overlay_plugin));
+    #[cfg(debug_assertions)]
+    app.add_plugins(WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::F4)));
+    app.run();
+}
+
+// everything but the window, renderer and audio, so the game can also run headless
+fn shmup_plugin(app: &mut App) {
+    app
+        .init_resource::<LaunchOptions>()
         .init_state::<GameState>()
         .add_systems(OnEnter(GameState::MainMenu), (
             despawn_screen::<GameObject>,
@@ -68,6 +129,13 @@ fn main() {
             despawn_screen::<GameObject>,
             clear_levels,
         ).chain())
+        .add_systems(OnTransition {
+            exited: GameState::PlayingGame,
+            entered: GameState::MainMenu,
+        }, (
+            despawn_screen::<GameObject>,
+            clear_levels,
+        ).chain())
         .add_systems(OnTransition {
             exited: GameState::GameOver,
             entered: GameState::MainMenu,
@@ -75,20 +143,48 @@ fn main() {
             despawn_screen::<GameObject>,
             clear_levels,
         ).chain())
+        .add_systems(OnTransition {
+            exited: GameState::Ending,
+            entered: GameState::MainMenu,
+        }, (
+            despawn_screen::<GameObject>,
+            clear_levels,
+        ).chain())
+        .add_systems(OnTransition {
+            exited: GameState::PlayingGame,
+            entered: GameState::PracticeMenu,
+        }, (
+            despawn_screen::<GameObject>,
+            clear_levels,
+        ).chain())
+        .add_systems(OnTransition {
+            exited: GameState::GameOver,
+            entered: GameState::PracticeMenu,
+        }, (
+            despawn_screen::<GameObject>,
+            clear_levels,
+        ).chain())
         .add_systems(OnEnter(GameState::Resetting), (
             despawn_screen::<GameObject>,
             clear_levels,
             restart_game,
         ).chain())
-        .add_systems(Startup, (setup, load_images, load_sprites, load_sounds).chain())
+        .add_systems(Startup, (setup, load_images, load_sprites, load_sounds, apply_launch_options).chain())
         .add_plugins((
+            settings_plugin,
+            input_plugin,
+            music_plugin,
             main_menu_plugin,
             game_plugin,
             game_over_menu_plugin,
             pause_menu_plugin,
+            replay_menu_plugin,
+            practice_menu_plugin,
+            options_menu_plugin,
+            key_config_menu_plugin,
+            music_room_menu_plugin,
         ))
-        // .add_plugins(WorldInspectorPlugin::new())
-        .run();
+    ;
 }
 
 
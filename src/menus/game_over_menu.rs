@@ -1,31 +1,55 @@
 use bevy::prelude::*;
 use crate::game::{FRAME_BORDER_BOTTOM, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
+use crate::input::Action;
 use crate::{despawn_screen, GameState};
-use crate::menus::{SELECTED_COLOR, UNSELECTED_COLOR};
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::UNSELECTED_COLOR;
 use crate::player::PlayerContinueEvent;
+use crate::player_stats::PlayerStats;
+use crate::practice::PracticeSession;
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
 
-#[derive(Resource)]
-struct GameOverMenuState {
-    options: Vec<Entity>,
-    selected: usize,
+const CONTINUE_SECONDS: u32 = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum GameOverMenuAction {
+    Continue,
+    Quit,
 }
 
 #[derive(Component)]
 struct OnGameOverScreen;
 
+// everything asking whether to continue, replaced by the final screen if the player doesn't
+#[derive(Component)]
+struct ContinuePrompt;
+
+#[derive(Component)]
+struct ContinueCountdown(Timer);
+
+#[derive(Component)]
+struct FinalGameOverText;
+
 pub fn game_over_menu_plugin(app: &mut App) {
     app
-        .add_systems(OnEnter(GameState::GameOver), game_over_menu_setup)
-        .add_systems(Update, (handle_input, draw).run_if(in_state(GameState::GameOver)))
+        .add_plugins(menu_plugin::<GameOverMenuAction>)
+        // a practice run goes straight back to the practice menu instead
+        .add_systems(OnEnter(GameState::GameOver), game_over_menu_setup.run_if(not(resource_exists::<PracticeSession>)))
+        .add_systems(Update, (
+            handle_menu_events,
+            count_down_to_game_over,
+            leave_final_screen,
+        ).chain().run_if(in_state(GameState::GameOver).and(not(resource_exists::<PracticeSession>))))
         .add_systems(OnExit(GameState::GameOver), despawn_screen::<OnGameOverScreen>)
     ;
 }
 
 fn game_over_menu_setup(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    asset_server: Res<AssetServer>,
+    player_stats: Res<PlayerStats>,
 ) {
     let shadow_width = FRAME_BORDER_RIGHT - FRAME_BORDER_LEFT + 50.0;
     let shadow_height = FRAME_BORDER_TOP - FRAME_BORDER_BOTTOM + 50.0;
@@ -39,79 +63,128 @@ fn game_over_menu_setup(
         OnGameOverScreen,
     ));
 
+    if player_stats.credits_left() == 0 {
+        spawn_final_screen(&mut commands, &asset_server, &player_stats);
+        return;
+    }
     let font = asset_server.load("fonts/Super-Cartoon.ttf");
-    let text_font = TextFont {
-        font: font.clone(),
-        font_size: 25.0,
-        ..default()
-    };
-
-    let continue_option_id = commands.spawn((
-        Name::new("ContinueMenuOption"),
-        Text2d::new("Continue"),
-        text_font.clone(),
-        Transform::from_xyz(FRAME_BORDER_LEFT + 100.0, 0.0, 0.99),
-        TextLayout::new_with_justify(JustifyText::Left),
-        TextColor(SELECTED_COLOR),
+    commands.spawn((
+        Name::new("ContinueText"),
+        Text2d::new(format!("Continue?\nCredits left: {}", player_stats.credits_left())),
+        TextFont {
+            font: font.clone(),
+            font_size: 30.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(frame_center.x, 130.0, 0.99),
+        TextColor(UNSELECTED_COLOR),
+        ContinuePrompt,
         OnGameOverScreen,
-    )).id();
-    let quit_option_id = commands.spawn((
-        Name::new("QuitMenuOption"),
-        Text2d::new("Quit to Menu"),
-        text_font.clone(),
-        Transform::from_xyz(FRAME_BORDER_LEFT + 125.0, -30.0, 0.99),
-        TextLayout::new_with_justify(JustifyText::Left),
+    ));
+    commands.spawn((
+        Name::new("ContinueCountdownText"),
+        Text2d::new(CONTINUE_SECONDS.to_string()),
+        TextFont {
+            font,
+            font_size: 60.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(frame_center.x, 60.0, 0.99),
         TextColor(UNSELECTED_COLOR),
+        ContinueCountdown(Timer::from_seconds(CONTINUE_SECONDS as f32, TimerMode::Once)),
+        ContinuePrompt,
+        OnGameOverScreen,
+    ));
+    commands.spawn((
+        Name::new("GameOverMenu"),
+        Menu::new(vec![
+            MenuItem::new("Continue", GameOverMenuAction::Continue),
+            MenuItem::new("Quit to Menu", GameOverMenuAction::Quit),
+        ], MenuStyle {
+            font_size: 25.0,
+            line_height: 30.0,
+            justify: JustifyText::Left,
+        }),
+        Transform::from_xyz(FRAME_BORDER_LEFT + 100.0, 0.0, 0.99),
+        Visibility::default(),
+        ContinuePrompt,
         OnGameOverScreen,
-    )).id();
-    commands.insert_resource(GameOverMenuState {
-        options: vec![continue_option_id, quit_option_id],
-        selected: 0
-    })
+    ));
 }
 
-fn handle_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    game_state: ResMut<NextState<GameState>>,
-    mut menu_state: ResMut<GameOverMenuState>,
-    player_continue_event_writer: EventWriter<PlayerContinueEvent>,
-) {
-    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
-        menu_state.selected = if menu_state.selected == 0 { menu_state.options.len() - 1 } else { menu_state.selected - 1 };
-    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
-        menu_state.selected = if menu_state.selected == menu_state.options.len() - 1 { 0 } else { menu_state.selected + 1 };
-    } else if keyboard_input.pressed(KeyCode::KeyZ) {
-        run_menu_action(menu_state.selected, game_state, player_continue_event_writer);
-    }
+fn spawn_final_screen(commands: &mut Commands, asset_server: &AssetServer, player_stats: &PlayerStats) {
+    commands.spawn((
+        Name::new("FinalGameOverText"),
+        Text2d::new(format!("Game Over\n\nScore {}", player_stats.score)),
+        TextFont {
+            font: asset_server.load("fonts/Super-Cartoon.ttf"),
+            font_size: 40.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(-128.0, 30.0, 0.99),
+        TextColor(UNSELECTED_COLOR),
+        FinalGameOverText,
+        OnGameOverScreen,
+    ));
 }
 
-fn run_menu_action(
-    menu_selected: usize,
+// the replay and any high score were saved as the run ended, so nothing after a continue is kept
+fn handle_menu_events(
+    mut menu_event_reader: EventReader<MenuEvent<GameOverMenuAction>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut player_continue_event_writer: EventWriter<PlayerContinueEvent>,
 ) {
-    match menu_selected {
-        0 => {
-            game_state.set(GameState::PlayingGame);
-            player_continue_event_writer.send(PlayerContinueEvent);
-        },
-        1 => {
-            game_state.set(GameState::MainMenu);
-        },
-        _ => {}
+    for menu_event in menu_event_reader.read() {
+        let MenuEvent::Confirm(action) = menu_event else {
+            continue;
+        };
+        match action {
+            GameOverMenuAction::Continue => {
+                game_state.set(GameState::PlayingGame);
+                player_continue_event_writer.send(PlayerContinueEvent);
+            },
+            GameOverMenuAction::Quit => game_state.set(GameState::MainMenu),
+        }
     }
 }
-fn draw(
-    menu_state: Res<GameOverMenuState>,
-    mut text2d_query: Query<(Entity, &mut TextColor)>,
+
+fn count_down_to_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time<Real>>,
+    player_stats: Res<PlayerStats>,
+    mut countdown_query: Query<(&mut ContinueCountdown, &mut Text2d)>,
+    prompt_query: Query<Entity, With<ContinuePrompt>>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
 ) {
-    for text_option in text2d_query.iter_mut() {
-        let text2d = text_option.0;
-        let mut text_color = text_option.1;
-        if text2d == menu_state.options[menu_state.selected] {
-            text_color.0 = SELECTED_COLOR;
-        } else {
-            text_color.0 = UNSELECTED_COLOR;
+    for (mut countdown, mut text) in countdown_query.iter_mut() {
+        if countdown.0.tick(time.delta()).just_finished() {
+            for entity in prompt_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            spawn_final_screen(&mut commands, &asset_server, &player_stats);
+            continue;
         }
+        let seconds = countdown.0.remaining_secs().ceil() as u32;
+        if text.0 != seconds.to_string() {
+            text.0 = seconds.to_string();
+            play_sound_event_writer.send(PlaySoundEvent(SoundEffect::TimerWarning));
+        }
+    }
+}
+
+fn leave_final_screen(
+    actions: Res<ButtonInput<Action>>,
+    final_text_query: Query<(), With<FinalGameOverText>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if final_text_query.is_empty() {
+        return;
+    }
+    if actions.just_pressed(Action::Confirm) || actions.just_pressed(Action::Cancel) {
+        game_state.set(GameState::MainMenu);
     }
 }
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use crate::{despawn_screen, GameState};
+use crate::input::{binding_name, key_code_label, Action, KeyBindings, PadBindings, BINDABLE_BUTTONS, BINDABLE_KEYS};
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::UNSELECTED_COLOR;
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, PartialEq)]
+enum KeyConfigAction {
+    Bind(Action),
+    Reset,
+    Back,
+}
+
+// the action waiting for the next key or gamepad button press, if any
+#[derive(Resource, Default)]
+struct KeyConfigState {
+    waiting: Option<Action>,
+}
+
+#[derive(Component)]
+struct OnKeyConfigScreen;
+
+pub fn key_config_menu_plugin(app: &mut App) {
+    app
+        .add_plugins(menu_plugin::<KeyConfigAction>)
+        .add_systems(OnEnter(GameState::KeyConfigMenu), key_config_menu_setup)
+        .add_systems(Update, (
+            capture_key,
+            handle_menu_events,
+            draw_bindings,
+        ).chain().run_if(in_state(GameState::KeyConfigMenu)))
+        .add_systems(OnExit(GameState::KeyConfigMenu), despawn_screen::<OnKeyConfigScreen>)
+    ;
+}
+
+fn key_config_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/Super-Cartoon.ttf");
+
+    commands.spawn((
+        Name::new("KeyConfigTitleText"),
+        Text2d::new("Controls"),
+        TextFont {
+            font,
+            font_size: 50.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 240.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        OnKeyConfigScreen,
+    ));
+    // the binding labels are filled in by draw_bindings
+    let mut items: Vec<MenuItem<KeyConfigAction>> = Action::ALL.into_iter()
+        .map(|action| MenuItem::new("", KeyConfigAction::Bind(action)))
+        .collect();
+    items.extend([
+        MenuItem::new("Reset to Defaults", KeyConfigAction::Reset),
+        MenuItem::new("Back", KeyConfigAction::Back),
+    ]);
+    commands.spawn((
+        Name::new("KeyConfigMenu"),
+        Menu::new(items, MenuStyle {
+            font_size: 25.0,
+            line_height: 34.0,
+            justify: JustifyText::Center,
+        }).with_cancel(KeyConfigAction::Back),
+        Transform::from_xyz(0.0, 180.0, 100.0),
+        Visibility::default(),
+        OnKeyConfigScreen,
+    ));
+    commands.insert_resource(KeyConfigState::default());
+}
+
+fn capture_key(
+    actions: Res<ButtonInput<Action>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_query: Query<&Gamepad>,
+    mut key_config: ResMut<KeyConfigState>,
+    mut settings: ResMut<Settings>,
+    mut menu_query: Query<&mut Menu<KeyConfigAction>>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    let Some(action) = key_config.waiting else {
+        // the menu only listens again once the new binding is let go, so it isn't read as a menu input too
+        let holding_key = keyboard.get_pressed().any(|key| BINDABLE_KEYS.contains(key));
+        let holding_button = gamepad_query.iter()
+            .any(|gamepad| gamepad.get_pressed().any(|button| BINDABLE_BUTTONS.contains(button)));
+        if !holding_key && !holding_button {
+            for mut menu in menu_query.iter_mut().filter(|menu| menu.locked) {
+                menu.locked = false;
+            }
+        }
+        return;
+    };
+    if actions.just_pressed(Action::Cancel) {
+        // backing out keeps the old binding
+    } else if let Some(key) = keyboard.get_just_pressed().find(|key| BINDABLE_KEYS.contains(key)) {
+        // a rejected binding keeps waiting for another
+        if !settings.key_bindings.bind(action, *key) {
+            return;
+        }
+    } else if let Some(button) = gamepad_query.iter()
+        .find_map(|gamepad| gamepad.get_just_pressed().find(|button| BINDABLE_BUTTONS.contains(button)).copied())
+    {
+        if !settings.pad_bindings.bind(action, button) {
+            return;
+        }
+    } else {
+        return;
+    }
+    key_config.waiting = None;
+    play_sound_event_writer.send(PlaySoundEvent(SoundEffect::MenuSelect));
+}
+
+fn handle_menu_events(
+    mut menu_event_reader: EventReader<MenuEvent<KeyConfigAction>>,
+    mut key_config: ResMut<KeyConfigState>,
+    mut settings: ResMut<Settings>,
+    mut menu_query: Query<&mut Menu<KeyConfigAction>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for menu_event in menu_event_reader.read() {
+        match menu_event {
+            MenuEvent::Confirm(KeyConfigAction::Bind(action)) => {
+                key_config.waiting = Some(*action);
+                for mut menu in menu_query.iter_mut() {
+                    menu.locked = true;
+                }
+            },
+            MenuEvent::Confirm(KeyConfigAction::Reset) => {
+                settings.key_bindings = KeyBindings::default();
+                settings.pad_bindings = PadBindings::default();
+            },
+            MenuEvent::Confirm(KeyConfigAction::Back) => game_state.set(GameState::OptionsMenu),
+            MenuEvent::Adjust(..) => {},
+        }
+    }
+}
+
+fn draw_bindings(
+    key_config: Res<KeyConfigState>,
+    settings: Res<Settings>,
+    mut menu_query: Query<&mut Menu<KeyConfigAction>>,
+) {
+    for mut menu in menu_query.iter_mut() {
+        if !menu.is_added() && !settings.is_changed() && !key_config.is_changed() {
+            continue;
+        }
+        for action in Action::ALL {
+            let binding = if key_config.waiting == Some(action) {
+                "...".to_string()
+            } else {
+                format!("{}  /  {}", key_code_label(settings.key_bindings.get(action)), binding_name(settings.pad_bindings.get(action)))
+            };
+            if let Some(item) = menu.item_mut(|candidate| *candidate == KeyConfigAction::Bind(action)) {
+                item.label = format!("{}  {}", action.name(), binding);
+            }
+        }
+    }
+}
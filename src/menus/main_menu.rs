@@ -1,23 +1,38 @@
 use bevy::{prelude::*};
 use crate::{despawn_screen, GameState};
-use crate::game::{ChosenLevel, LevelState};
-use crate::menus::{SELECTED_COLOR, UNSELECTED_COLOR};
-use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
+use crate::game::{Checkpoint, ChosenLevel, Difficulty, LevelState};
+use crate::high_scores::HighScores;
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::options_menu::OptionsMenuReturn;
+use crate::menus::UNSELECTED_COLOR;
+
+#[derive(Clone, Copy, PartialEq)]
+enum MainMenuAction {
+    Play,
+    Difficulty,
+    Practice,
+    Replays,
+    Options,
+    MusicRoom,
+    Quit,
+}
 
-// implement menu as a vector of MainMenuStates
 #[derive(Resource)]
 struct MainMenuState {
-    options: Vec<Entity>,
-    selected: usize,
+    high_scores: HighScores,
 }
 
 #[derive(Component)]
 struct OnMainMenuScreen;
 
+#[derive(Component)]
+struct HighScoreText;
+
 pub fn main_menu_plugin(app: &mut App) {
     app
+        .add_plugins(menu_plugin::<MainMenuAction>)
         .add_systems(OnEnter(GameState::MainMenu), main_menu_setup)
-        .add_systems(Update, (handle_input, draw).run_if(in_state(GameState::MainMenu)))
+        .add_systems(Update, (handle_menu_events, draw_difficulty).chain().run_if(in_state(GameState::MainMenu)))
         .add_systems(OnExit(GameState::MainMenu), (
             despawn_screen::<OnMainMenuScreen>,
         ).chain())
@@ -29,99 +44,95 @@ fn main_menu_setup(
     asset_server: Res<AssetServer>,
 ) {
     let font = asset_server.load("fonts/Super-Cartoon.ttf");
-    let text_font = TextFont {
-        font: font.clone(),
-        font_size: 50.0,
-        ..default()
-    };
-    let text_justification = JustifyText::Center;
 
-    let test_option_id = commands.spawn((
-        Name::new("TestText"),
+    commands.spawn((
+        Name::new("HighScoreText"),
         StateScoped(GameState::MainMenu),
-        Text2d::new("Test"),
-        text_font.clone(),
-        TextLayout::new_with_justify(text_justification),
-        TextColor(SELECTED_COLOR),
-        OnMainMenuScreen,
-    )).id();
-    let play_option_id = commands.spawn((
-        Name::new("PlayText"),
-        StateScoped(GameState::MainMenu),
-        Text2d::new("Play"),
-        text_font.clone(),
-        TextLayout::new_with_justify(text_justification),
-        Transform::from_xyz(0.0, -50.0, 100.0),
+        Text2d::new(""),
+        TextFont {
+            font,
+            font_size: 25.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 100.0, 100.0),
         TextColor(UNSELECTED_COLOR),
+        HighScoreText,
         OnMainMenuScreen,
-    )).id();
-    let quit_option_id = commands.spawn((
-        Name::new("QuitText"),
+    ));
+    commands.spawn((
+        Name::new("MainMenu"),
         StateScoped(GameState::MainMenu),
-        Text2d::new("Quit"),
-        text_font.clone(),
-        TextLayout::new_with_justify(text_justification),
-        Transform::from_xyz(0.0, -100.0, 100.0),
-        TextColor(UNSELECTED_COLOR),
+        Menu::new(vec![
+            MenuItem::new("Play", MainMenuAction::Play),
+            // the label is filled in by draw_difficulty
+            MenuItem::new("", MainMenuAction::Difficulty).adjustable(),
+            MenuItem::new("Practice", MainMenuAction::Practice),
+            MenuItem::new("Replays", MainMenuAction::Replays),
+            MenuItem::new("Options", MainMenuAction::Options),
+            MenuItem::new("Music Room", MainMenuAction::MusicRoom),
+            MenuItem::new("Quit", MainMenuAction::Quit),
+        ], MenuStyle {
+            font_size: 50.0,
+            line_height: 45.0,
+            justify: JustifyText::Center,
+        }),
+        Transform::from_xyz(0.0, 25.0, 100.0),
+        Visibility::default(),
         OnMainMenuScreen,
-    )).id();
+    ));
     commands.insert_resource(MainMenuState {
-        options: vec![test_option_id, play_option_id, quit_option_id],
-        selected: 0
+        high_scores: HighScores::load(),
     });
 }
 
-fn handle_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut menu_state: ResMut<MainMenuState>,
-    app_exit_events: EventWriter<AppExit>,
-    game_state: ResMut<NextState<GameState>>,
-    chosen_level: ResMut<ChosenLevel>,
-    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
-) {
-    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
-        menu_state.selected = if menu_state.selected == 0 { menu_state.options.len() - 1 } else { menu_state.selected - 1 };
-        play_sound_event_writer.send(PlaySoundEvent(SoundEffect::MenuSelect));
-    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
-        menu_state.selected = if menu_state.selected == menu_state.options.len() - 1 { 0 } else { menu_state.selected + 1 };
-        play_sound_event_writer.send(PlaySoundEvent(SoundEffect::MenuSelect));
-    } else if keyboard_input.just_pressed(KeyCode::KeyZ) {
-        run_main_menu_action(menu_state.selected, app_exit_events, game_state, chosen_level);
-        play_sound_event_writer.send(PlaySoundEvent(SoundEffect::MenuSelect));
-    }
-}
-
-fn run_main_menu_action(
-    menu_selected: usize,
+fn handle_menu_events(
+    mut menu_event_reader: EventReader<MenuEvent<MainMenuAction>>,
     mut app_exit_events: EventWriter<AppExit>,
     mut game_state: ResMut<NextState<GameState>>,
     mut chosen_level: ResMut<ChosenLevel>,
+    mut difficulty: ResMut<Difficulty>,
+    mut options_menu_return: ResMut<OptionsMenuReturn>,
 ) {
-    match menu_selected {
-        0 => {
-            game_state.set(GameState::StartingGame);
-            chosen_level.level = LevelState::TestBed;
-        },
-        1 => {
-            game_state.set(GameState::StartingGame);
-            chosen_level.level = LevelState::Level1;
-        },
-        2 => { app_exit_events.send(AppExit::Success); },
-        _ => {}
+    for menu_event in menu_event_reader.read() {
+        match menu_event {
+            MenuEvent::Adjust(MainMenuAction::Difficulty, step) => {
+                *difficulty = if *step < 0 { difficulty.easier() } else { difficulty.harder() };
+            },
+            MenuEvent::Confirm(MainMenuAction::Difficulty) => *difficulty = difficulty.harder(),
+            MenuEvent::Confirm(MainMenuAction::Play) => {
+                game_state.set(GameState::StartingGame);
+                chosen_level.level = LevelState::Level1;
+                chosen_level.checkpoint = Checkpoint::Start;
+            },
+            MenuEvent::Confirm(MainMenuAction::Practice) => game_state.set(GameState::PracticeMenu),
+            MenuEvent::Confirm(MainMenuAction::Replays) => game_state.set(GameState::ReplayMenu),
+            MenuEvent::Confirm(MainMenuAction::Options) => {
+                game_state.set(GameState::OptionsMenu);
+                options_menu_return.0 = GameState::MainMenu;
+            },
+            MenuEvent::Confirm(MainMenuAction::MusicRoom) => game_state.set(GameState::MusicRoom),
+            MenuEvent::Confirm(MainMenuAction::Quit) => { app_exit_events.send(AppExit::Success); },
+            MenuEvent::Adjust(..) => {},
+        }
     }
 }
 
-fn draw(
+fn draw_difficulty(
     menu_state: Res<MainMenuState>,
-    mut text2d_query: Query<(Entity, &mut TextColor)>,
+    difficulty: Res<Difficulty>,
+    mut menu_query: Query<&mut Menu<MainMenuAction>>,
+    mut high_score_query: Query<&mut Text2d, With<HighScoreText>>,
 ) {
-    for text_option in text2d_query.iter_mut() {
-        let text2d = text_option.0;
-        let mut text_color = text_option.1;
-        if text2d == menu_state.options[menu_state.selected] {
-            text_color.0 = SELECTED_COLOR;
-        } else {
-            text_color.0 = UNSELECTED_COLOR;
+    if !difficulty.is_changed() && !menu_state.is_changed() {
+        return;
+    }
+    for mut menu in menu_query.iter_mut() {
+        if let Some(item) = menu.item_mut(|action| *action == MainMenuAction::Difficulty) {
+            item.label = format!("< {} >", difficulty.name());
         }
     }
+    for mut text in high_score_query.iter_mut() {
+        text.0 = format!("Hi-Score {}", menu_state.high_scores.best(*difficulty));
+    }
 }
@@ -0,0 +1,371 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::input::Action;
+use crate::menus::{DISABLED_COLOR, SELECTED_COLOR, UNSELECTED_COLOR};
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
+
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const REPEAT_INTERVAL: Duration = Duration::from_millis(80);
+
+// anything a menu item can hand back to the menu's owner when it's chosen
+pub trait MenuAction: Clone + Send + Sync + 'static {}
+
+impl<A: Clone + Send + Sync + 'static> MenuAction for A {}
+
+pub enum MenuItemKind<A> {
+    Action(A),
+    Submenu(Vec<MenuItem<A>>),
+    Back,
+}
+
+pub struct MenuItem<A> {
+    pub label: String,
+    pub kind: MenuItemKind<A>,
+    pub enabled: bool,
+    // left and right change this item's value instead of doing nothing
+    pub adjustable: bool,
+}
+
+impl<A> MenuItem<A> {
+    pub fn new(label: impl Into<String>, action: A) -> Self {
+        Self::with_kind(label, MenuItemKind::Action(action))
+    }
+
+    pub fn submenu(label: impl Into<String>, items: Vec<MenuItem<A>>) -> Self {
+        Self::with_kind(label, MenuItemKind::Submenu(items))
+    }
+
+    pub fn back(label: impl Into<String>) -> Self {
+        Self::with_kind(label, MenuItemKind::Back)
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn adjustable(mut self) -> Self {
+        self.adjustable = true;
+        self
+    }
+
+    fn with_kind(label: impl Into<String>, kind: MenuItemKind<A>) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+            enabled: true,
+            adjustable: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct MenuStyle {
+    pub font_size: f32,
+    pub line_height: f32,
+    pub justify: JustifyText,
+}
+
+// what the player did with a menu, for the system that owns it to act on
+#[derive(Event)]
+pub enum MenuEvent<A: MenuAction> {
+    Confirm(A),
+    Adjust(A, i32),
+}
+
+// a list of items drawn as children of this entity, and the only text it recolors
+#[derive(Component)]
+pub struct Menu<A: MenuAction> {
+    pub items: Vec<MenuItem<A>>,
+    pub style: MenuStyle,
+    // sent when backing out of the top level, as if that item had been chosen
+    pub cancel: Option<A>,
+    // ignores input while set, for when another system is reading keys itself
+    pub locked: bool,
+    // the submenu item chosen on each level above the current one, then the selection on the current one
+    stack: Vec<usize>,
+}
+
+impl<A: MenuAction> Menu<A> {
+    pub fn new(items: Vec<MenuItem<A>>, style: MenuStyle) -> Self {
+        let mut menu = Self {
+            items,
+            style,
+            cancel: None,
+            locked: false,
+            stack: vec![0],
+        };
+        menu.select_first_enabled();
+        menu
+    }
+
+    pub fn with_cancel(mut self, action: A) -> Self {
+        self.cancel = Some(action);
+        self
+    }
+
+    pub fn page(&self) -> &[MenuItem<A>] {
+        let mut items = &self.items;
+        for index in &self.stack[..self.stack.len() - 1] {
+            if let MenuItemKind::Submenu(submenu) = &items[*index].kind {
+                items = submenu;
+            }
+        }
+        items
+    }
+
+    fn page_mut(&mut self) -> &mut Vec<MenuItem<A>> {
+        let mut items = &mut self.items;
+        for index in &self.stack[..self.stack.len() - 1] {
+            // check before borrowing so the borrow only ends up in `items` when it descends
+            if !matches!(items[*index].kind, MenuItemKind::Submenu(_)) {
+                continue;
+            }
+            let MenuItemKind::Submenu(submenu) = &mut items[*index].kind else {
+                unreachable!()
+            };
+            items = submenu;
+        }
+        items
+    }
+
+    pub fn selected(&self) -> usize {
+        *self.stack.last().unwrap()
+    }
+
+    pub fn select(&mut self, index: usize) {
+        *self.stack.last_mut().unwrap() = index;
+    }
+
+    pub fn is_in_submenu(&self) -> bool {
+        self.stack.len() > 1
+    }
+
+    // finds an item on the current page by its action, to relabel or enable it
+    pub fn item_mut(&mut self, is_action: impl Fn(&A) -> bool) -> Option<&mut MenuItem<A>> {
+        self.page_mut().iter_mut().find(|item| matches!(&item.kind, MenuItemKind::Action(action) if is_action(action)))
+    }
+
+    // moves the selection, wrapping around and skipping disabled items
+    pub fn move_selection(&mut self, step: i32) {
+        let count = self.page().len() as i32;
+        let mut index = self.selected() as i32;
+        for _ in 0..count {
+            index = (index + step).rem_euclid(count);
+            if self.page()[index as usize].enabled {
+                self.select(index as usize);
+                return;
+            }
+        }
+    }
+
+    // returns the action to send, if choosing the item doesn't just open or close a page
+    pub fn confirm(&mut self) -> Option<A> {
+        let selected = self.selected();
+        let item = self.page().get(selected)?;
+        if !item.enabled {
+            return None;
+        }
+        match &item.kind {
+            MenuItemKind::Action(action) => Some(action.clone()),
+            MenuItemKind::Submenu(_) => {
+                self.stack.push(0);
+                self.select_first_enabled();
+                None
+            },
+            MenuItemKind::Back => self.back(),
+        }
+    }
+
+    pub fn back(&mut self) -> Option<A> {
+        if self.is_in_submenu() {
+            self.stack.pop();
+            None
+        } else {
+            self.cancel.clone()
+        }
+    }
+
+    fn select_first_enabled(&mut self) {
+        if let Some(index) = self.page().iter().position(|item| item.enabled) {
+            self.select(index);
+        }
+    }
+}
+
+#[derive(Component)]
+struct MenuItemText;
+
+// the held direction, so holding it keeps moving after a short delay
+#[derive(Component, Default)]
+struct MenuRepeat {
+    action: Option<Action>,
+    timer: Timer,
+}
+
+pub fn menu_plugin<A: MenuAction>(app: &mut App) {
+    app
+        .add_event::<MenuEvent<A>>()
+        .add_systems(Update, (
+            add_menu_repeat::<A>,
+            navigate_menus::<A>,
+            draw_menus::<A>,
+        ).chain())
+    ;
+}
+
+fn add_menu_repeat<A: MenuAction>(
+    mut commands: Commands,
+    menu_query: Query<Entity, (With<Menu<A>>, Without<MenuRepeat>)>,
+) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).insert(MenuRepeat::default());
+    }
+}
+
+fn repeated_press(actions: &ButtonInput<Action>, action: Action, repeat: &mut MenuRepeat, delta: Duration) -> bool {
+    if actions.just_pressed(action) {
+        repeat.action = Some(action);
+        repeat.timer = Timer::new(REPEAT_DELAY, TimerMode::Once);
+        return true;
+    }
+    if repeat.action == Some(action) && actions.pressed(action) && repeat.timer.tick(delta).just_finished() {
+        repeat.timer = Timer::new(REPEAT_INTERVAL, TimerMode::Once);
+        return true;
+    }
+    false
+}
+
+fn navigate_menus<A: MenuAction>(
+    actions: Res<ButtonInput<Action>>,
+    time: Res<Time<Real>>,
+    mut menu_query: Query<(&mut Menu<A>, &mut MenuRepeat)>,
+    mut menu_event_writer: EventWriter<MenuEvent<A>>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    for (mut menu, mut repeat) in menu_query.iter_mut() {
+        if menu.locked {
+            continue;
+        }
+        let mut pressed = |action| repeated_press(&actions, action, &mut repeat, time.delta());
+        let up = pressed(Action::Up);
+        let down = pressed(Action::Down);
+        let left = pressed(Action::Left);
+        let right = pressed(Action::Right);
+
+        if up || down {
+            menu.move_selection(if up { -1 } else { 1 });
+        } else if left || right {
+            let Some(item) = menu.page().get(menu.selected()) else {
+                continue;
+            };
+            let MenuItemKind::Action(action) = &item.kind else {
+                continue;
+            };
+            if !item.adjustable || !item.enabled {
+                continue;
+            }
+            menu_event_writer.send(MenuEvent::Adjust(action.clone(), if left { -1 } else { 1 }));
+        } else if actions.just_pressed(Action::Confirm) {
+            if let Some(action) = menu.confirm() {
+                menu_event_writer.send(MenuEvent::Confirm(action));
+            }
+        } else if actions.just_pressed(Action::Cancel) || actions.just_pressed(Action::Pause) {
+            if let Some(action) = menu.back() {
+                menu_event_writer.send(MenuEvent::Confirm(action));
+            }
+        } else {
+            continue;
+        }
+        play_sound_event_writer.send(PlaySoundEvent(SoundEffect::MenuSelect));
+    }
+}
+
+fn draw_menus<A: MenuAction>(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    menu_query: Query<(Entity, &Menu<A>, Option<&Children>), Changed<Menu<A>>>,
+    item_text_query: Query<(), With<MenuItemText>>,
+) {
+    for (entity, menu, children) in menu_query.iter() {
+        for child in children.into_iter().flatten() {
+            if item_text_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        let text_font = TextFont {
+            font: asset_server.load("fonts/Super-Cartoon.ttf"),
+            font_size: menu.style.font_size,
+            ..default()
+        };
+        commands.entity(entity).with_children(|parent| {
+            for (i, item) in menu.page().iter().enumerate() {
+                let color = if !item.enabled {
+                    DISABLED_COLOR
+                } else if i == menu.selected() {
+                    SELECTED_COLOR
+                } else {
+                    UNSELECTED_COLOR
+                };
+                parent.spawn((
+                    Name::new("MenuItemText"),
+                    Text2d::new(item.label.clone()),
+                    text_font.clone(),
+                    TextLayout::new_with_justify(menu.style.justify),
+                    Transform::from_xyz(0.0, -(i as f32) * menu.style.line_height, 0.0),
+                    TextColor(color),
+                    MenuItemText,
+                ));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLE: MenuStyle = MenuStyle {
+        font_size: 10.0,
+        line_height: 10.0,
+        justify: JustifyText::Left,
+    };
+
+    #[test]
+    fn navigation_wraps_around_and_skips_disabled_items() {
+        let mut menu = Menu::new(vec![
+            MenuItem::new("Off", 0).enabled(false),
+            MenuItem::new("First", 1),
+            MenuItem::new("Second", 2),
+        ], STYLE);
+        assert_eq!(menu.selected(), 1);
+
+        menu.move_selection(1);
+        assert_eq!(menu.selected(), 2);
+        menu.move_selection(1);
+        assert_eq!(menu.selected(), 1);
+        menu.move_selection(-1);
+        assert_eq!(menu.selected(), 2);
+    }
+
+    #[test]
+    fn submenus_open_and_back_out_to_the_cancel_action() {
+        let mut menu = Menu::new(vec![
+            MenuItem::submenu("More", vec![
+                MenuItem::new("Inner", 1),
+                MenuItem::back("Back"),
+            ]),
+            MenuItem::new("Outer", 2),
+        ], STYLE).with_cancel(3);
+
+        assert_eq!(menu.confirm(), None);
+        assert!(menu.is_in_submenu());
+        assert_eq!(menu.confirm(), Some(1));
+
+        menu.move_selection(1);
+        assert_eq!(menu.confirm(), None);
+        assert!(!menu.is_in_submenu());
+        assert_eq!(menu.selected(), 0);
+        assert_eq!(menu.back(), Some(3));
+    }
+}
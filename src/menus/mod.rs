@@ -1,9 +1,15 @@
 use bevy::color::Color;
 
 pub mod game_over_menu;
+pub mod key_config_menu;
 pub mod main_menu;
+pub mod menu;
+pub mod music_room_menu;
+pub mod options_menu;
 pub mod pause_menu;
+pub mod practice_menu;
+pub mod replay_menu;
 
 const SELECTED_COLOR: Color = Color::srgb(0.9, 0.0, 0.9);
 const UNSELECTED_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
-
+const DISABLED_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
@@ -0,0 +1,142 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::{despawn_screen, GameState};
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::UNSELECTED_COLOR;
+use crate::music::{CurrentMusic, MusicPosition, MusicTrack, MusicUnlocks, PlayMusicEvent};
+
+#[derive(Clone, Copy, PartialEq)]
+enum MusicRoomAction {
+    Play(MusicTrack),
+    Back,
+}
+
+#[derive(Component)]
+struct OnMusicRoomScreen;
+
+#[derive(Component)]
+struct TrackCommentText;
+
+#[derive(Component)]
+struct TrackPositionText;
+
+pub fn music_room_menu_plugin(app: &mut App) {
+    app
+        .add_plugins(menu_plugin::<MusicRoomAction>)
+        .add_systems(OnEnter(GameState::MusicRoom), music_room_setup)
+        .add_systems(Update, (handle_menu_events, draw_now_playing).chain().run_if(in_state(GameState::MusicRoom)))
+        .add_systems(OnExit(GameState::MusicRoom), despawn_screen::<OnMusicRoomScreen>)
+    ;
+}
+
+fn music_room_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/Super-Cartoon.ttf");
+
+    commands.spawn((
+        Name::new("MusicRoomTitleText"),
+        Text2d::new("Music Room"),
+        TextFont {
+            font: font.clone(),
+            font_size: 50.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 240.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        OnMusicRoomScreen,
+    ));
+    // tracks not heard in play yet stay hidden
+    let unlocks = MusicUnlocks::load();
+    let mut items: Vec<MenuItem<MusicRoomAction>> = MusicTrack::ALL.into_iter().enumerate()
+        .map(|(i, track)| {
+            let unlocked = unlocks.is_unlocked(track);
+            let title = if unlocked { track.info().title } else { "???" };
+            MenuItem::new(format!("{:02}. {}", i + 1, title), MusicRoomAction::Play(track)).enabled(unlocked)
+        })
+        .collect();
+    items.push(MenuItem::new("Back", MusicRoomAction::Back));
+    commands.spawn((
+        Name::new("MusicRoomMenu"),
+        Menu::new(items, MenuStyle {
+            font_size: 25.0,
+            line_height: 34.0,
+            justify: JustifyText::Center,
+        }).with_cancel(MusicRoomAction::Back),
+        Transform::from_xyz(0.0, 170.0, 100.0),
+        Visibility::default(),
+        OnMusicRoomScreen,
+    ));
+    commands.spawn((
+        Name::new("TrackCommentText"),
+        Text2d::new(""),
+        TextFont {
+            font: font.clone(),
+            font_size: 20.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, -90.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        TrackCommentText,
+        OnMusicRoomScreen,
+    ));
+    commands.spawn((
+        Name::new("TrackPositionText"),
+        Text2d::new(""),
+        TextFont {
+            font,
+            font_size: 25.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, -220.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        TrackPositionText,
+        OnMusicRoomScreen,
+    ));
+}
+
+fn handle_menu_events(
+    mut menu_event_reader: EventReader<MenuEvent<MusicRoomAction>>,
+    mut play_music_event_writer: EventWriter<PlayMusicEvent>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for menu_event in menu_event_reader.read() {
+        match menu_event {
+            MenuEvent::Confirm(MusicRoomAction::Play(track)) => { play_music_event_writer.send(PlayMusicEvent(*track)); },
+            MenuEvent::Confirm(MusicRoomAction::Back) => game_state.set(GameState::MainMenu),
+            MenuEvent::Adjust(..) => {},
+        }
+    }
+}
+
+fn draw_now_playing(
+    current_music: Res<CurrentMusic>,
+    music_position: Res<MusicPosition>,
+    mut comment_query: Query<&mut Text2d, (With<TrackCommentText>, Without<TrackPositionText>)>,
+    mut position_query: Query<&mut Text2d, (With<TrackPositionText>, Without<TrackCommentText>)>,
+) {
+    let Some(track) = current_music.0 else {
+        return;
+    };
+    let info = track.info();
+    let position = match info.loop_end {
+        Some(loop_end) => format!("{}  {} / {}", info.title, minutes_and_seconds(music_position.0), minutes_and_seconds(loop_end)),
+        None => format!("{}  {}", info.title, minutes_and_seconds(music_position.0)),
+    };
+    // only touching the text when it changes keeps it from being laid out again every frame
+    for mut text in comment_query.iter_mut().filter(|text| text.0 != info.comment) {
+        text.0 = info.comment.to_string();
+    }
+    for mut text in position_query.iter_mut().filter(|text| text.0 != position) {
+        text.0 = position.clone();
+    }
+}
+
+fn minutes_and_seconds(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
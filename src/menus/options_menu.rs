@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use crate::{despawn_screen, GameState};
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::UNSELECTED_COLOR;
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, PartialEq)]
+enum OptionsMenuAction {
+    BgmVolume,
+    SfxVolume,
+    WindowScale,
+    Fullscreen,
+    Vsync,
+    PracticeLives,
+    StickDeadzone,
+    EightWayStick,
+    KeyConfig,
+    Back,
+}
+
+// where Back goes, since the options can be opened from the pause menu in the middle of a run
+#[derive(Resource)]
+pub struct OptionsMenuReturn(pub GameState);
+
+impl Default for OptionsMenuReturn {
+    fn default() -> Self {
+        Self(GameState::MainMenu)
+    }
+}
+
+#[derive(Component)]
+struct OnOptionsMenuScreen;
+
+pub fn options_menu_plugin(app: &mut App) {
+    app
+        .add_plugins(menu_plugin::<OptionsMenuAction>)
+        .init_resource::<OptionsMenuReturn>()
+        .add_systems(OnEnter(GameState::OptionsMenu), options_menu_setup)
+        .add_systems(Update, (handle_menu_events, draw_settings).chain().run_if(in_state(GameState::OptionsMenu)))
+        .add_systems(OnExit(GameState::OptionsMenu), despawn_screen::<OnOptionsMenuScreen>)
+    ;
+}
+
+fn options_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/Super-Cartoon.ttf");
+
+    commands.spawn((
+        Name::new("OptionsTitleText"),
+        Text2d::new("Options"),
+        TextFont {
+            font,
+            font_size: 50.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 220.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        OnOptionsMenuScreen,
+    ));
+    // the labels are filled in by draw_settings
+    commands.spawn((
+        Name::new("OptionsMenu"),
+        Menu::new(vec![
+            MenuItem::new("", OptionsMenuAction::BgmVolume).adjustable(),
+            MenuItem::new("", OptionsMenuAction::SfxVolume).adjustable(),
+            MenuItem::new("", OptionsMenuAction::WindowScale).adjustable(),
+            MenuItem::new("", OptionsMenuAction::Fullscreen).adjustable(),
+            MenuItem::new("", OptionsMenuAction::Vsync).adjustable(),
+            MenuItem::new("", OptionsMenuAction::PracticeLives).adjustable(),
+            MenuItem::new("", OptionsMenuAction::StickDeadzone).adjustable(),
+            MenuItem::new("", OptionsMenuAction::EightWayStick).adjustable(),
+            MenuItem::new("Controls", OptionsMenuAction::KeyConfig),
+            MenuItem::new("Back", OptionsMenuAction::Back),
+        ], MenuStyle {
+            font_size: 28.0,
+            line_height: 36.0,
+            justify: JustifyText::Center,
+        }).with_cancel(OptionsMenuAction::Back),
+        Transform::from_xyz(0.0, 160.0, 100.0),
+        Visibility::default(),
+        OnOptionsMenuScreen,
+    ));
+}
+
+fn handle_menu_events(
+    mut menu_event_reader: EventReader<MenuEvent<OptionsMenuAction>>,
+    mut settings: ResMut<Settings>,
+    options_menu_return: Res<OptionsMenuReturn>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for menu_event in menu_event_reader.read() {
+        match menu_event {
+            MenuEvent::Adjust(OptionsMenuAction::BgmVolume, step) => settings.change_bgm_volume(*step),
+            MenuEvent::Adjust(OptionsMenuAction::SfxVolume, step) => settings.change_sfx_volume(*step),
+            MenuEvent::Adjust(OptionsMenuAction::WindowScale, step) => settings.change_window_scale(*step),
+            MenuEvent::Adjust(OptionsMenuAction::PracticeLives, step) => settings.change_practice_lives(*step),
+            MenuEvent::Adjust(OptionsMenuAction::StickDeadzone, step) => settings.change_deadzone(*step),
+            MenuEvent::Adjust(OptionsMenuAction::Fullscreen, _) | MenuEvent::Confirm(OptionsMenuAction::Fullscreen) => {
+                settings.fullscreen = !settings.fullscreen;
+            },
+            MenuEvent::Adjust(OptionsMenuAction::Vsync, _) | MenuEvent::Confirm(OptionsMenuAction::Vsync) => {
+                settings.vsync = !settings.vsync;
+            },
+            MenuEvent::Adjust(OptionsMenuAction::EightWayStick, _) | MenuEvent::Confirm(OptionsMenuAction::EightWayStick) => {
+                settings.eight_way_stick = !settings.eight_way_stick;
+            },
+            MenuEvent::Confirm(OptionsMenuAction::KeyConfig) => game_state.set(GameState::KeyConfigMenu),
+            MenuEvent::Confirm(OptionsMenuAction::Back) => game_state.set(options_menu_return.0),
+            _ => {},
+        }
+    }
+}
+
+fn draw_settings(
+    settings: Res<Settings>,
+    mut menu_query: Query<&mut Menu<OptionsMenuAction>>,
+) {
+    for mut menu in menu_query.iter_mut() {
+        if !menu.is_added() && !settings.is_changed() {
+            continue;
+        }
+        let on_off = |value| if value { "On" } else { "Off" };
+        let labels = [
+            (OptionsMenuAction::BgmVolume, format!("BGM Volume  < {} >", settings.bgm_volume)),
+            (OptionsMenuAction::SfxVolume, format!("SFX Volume  < {} >", settings.sfx_volume)),
+            (OptionsMenuAction::WindowScale, format!("Window Scale  < {}x >", settings.window_scale)),
+            (OptionsMenuAction::Fullscreen, format!("Fullscreen  < {} >", on_off(settings.fullscreen))),
+            (OptionsMenuAction::Vsync, format!("VSync  < {} >", on_off(settings.vsync))),
+            (OptionsMenuAction::PracticeLives, format!("Practice Lives  < {} >", settings.practice_lives)),
+            (OptionsMenuAction::StickDeadzone, format!("Stick Deadzone  < {}% >", settings.deadzone_percent)),
+            (OptionsMenuAction::EightWayStick, format!("8-Way Stick  < {} >", on_off(settings.eight_way_stick))),
+        ];
+        for (row, label) in labels {
+            if let Some(item) = menu.item_mut(|action| *action == row) {
+                item.label = label;
+            }
+        }
+    }
+}
@@ -1,22 +1,57 @@
+use std::time::Duration;
 use bevy::prelude::*;
-use crate::game::{FRAME_BORDER_BOTTOM, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
-use crate::{despawn_screen, GameState};
-use crate::menus::{SELECTED_COLOR, UNSELECTED_COLOR};
+use crate::bosses::rumia::RumiaState;
+use crate::game::{Checkpoint, ChosenLevel, GameObject, FRAME_BORDER_BOTTOM, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
+use crate::level1::{current_checkpoint, FirstLevelState};
+use crate::{despawn_screen, GameState, DEFAULT_RESOLUTION};
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::options_menu::OptionsMenuReturn;
+use crate::menus::UNSELECTED_COLOR;
+use crate::practice::PracticeSession;
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
 
-#[derive(Resource)]
-struct PauseMenuState {
-    options: Vec<Entity>,
-    selected: usize,
+const COUNTDOWN_STEPS: u32 = 3;
+const COUNTDOWN_STEP: Duration = Duration::from_millis(400);
+
+#[derive(Clone, Copy, PartialEq)]
+enum PauseMenuAction {
+    Resume,
+    RetrySection,
+    Restart,
+    Options,
+    Quit,
 }
 
 #[derive(Component)]
 struct OnPauseScreen;
 
+// the shadow and menu, cleared away while counting down to resume
+#[derive(Component)]
+struct PauseMenuContent;
+
+// hides the run behind the options screens opened from the pause menu
+#[derive(Component)]
+struct PauseOptionsBackdrop;
+
+#[derive(Component)]
+struct ResumeCountdown(Timer);
+
 pub fn pause_menu_plugin(app: &mut App) {
     app
-        .add_systems(OnEnter(GameState::Paused), pause_menu_setup)
-        .add_systems(Update, (handle_input, draw).run_if(in_state(GameState::Paused)))
+        .add_plugins(menu_plugin::<PauseMenuAction>)
+        .add_systems(OnEnter(GameState::Paused), (
+            despawn_screen::<PauseOptionsBackdrop>,
+            pause_menu_setup,
+        ))
+        .add_systems(Update, (
+            handle_menu_events,
+            count_down_to_resume,
+        ).chain().run_if(in_state(GameState::Paused)))
         .add_systems(OnExit(GameState::Paused), despawn_screen::<OnPauseScreen>)
+        .add_systems(OnTransition {
+            exited: GameState::Paused,
+            entered: GameState::OptionsMenu,
+        }, spawn_options_backdrop)
     ;
 }
 
@@ -24,7 +59,6 @@ fn pause_menu_setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    asset_server: Res<AssetServer>,
 ) {
 
     let shadow_width = FRAME_BORDER_RIGHT - FRAME_BORDER_LEFT + 50.0;
@@ -36,90 +70,124 @@ fn pause_menu_setup(
         Mesh2d(shadow),
         MeshMaterial2d(materials.add(Color::srgba(0.0, 0.0, 0.0, 0.75))),
         Transform::from_xyz(frame_center.x, frame_center.y, 0.98),
+        PauseMenuContent,
         OnPauseScreen,
     ));
 
-    let font = asset_server.load("fonts/Super-Cartoon.ttf");
-    let text_font = TextFont {
-        font: font.clone(),
-        font_size: 25.0,
-        ..default()
-    };
-
-    let continue_option_id = commands.spawn((
-        Name::new("ResumeMenuOption"),
-        Text2d::new("Resume"),
-        text_font.clone(),
+    commands.spawn((
+        Name::new("PauseMenu"),
+        Menu::new(vec![
+            MenuItem::new("Resume", PauseMenuAction::Resume),
+            confirmation("Retry Section", "Retry from this section?", PauseMenuAction::RetrySection),
+            confirmation("Restart", "Restart from the beginning?", PauseMenuAction::Restart),
+            MenuItem::new("Options", PauseMenuAction::Options),
+            confirmation("Quit to Menu", "Quit this run?", PauseMenuAction::Quit),
+        ], MenuStyle {
+            font_size: 25.0,
+            line_height: 30.0,
+            justify: JustifyText::Left,
+        }).with_cancel(PauseMenuAction::Resume),
         Transform::from_xyz(FRAME_BORDER_LEFT + 100.0, 0.0, 0.99),
-        TextLayout::new_with_justify(JustifyText::Left),
-        TextColor(SELECTED_COLOR),
-        OnPauseScreen,
-    )).id();
-    let restart_option_id = commands.spawn((
-        Name::new("RestartMenuOption"),
-        Text2d::new("Restart"),
-        text_font.clone(),
-        Transform::from_xyz(FRAME_BORDER_LEFT + 100.0, -30.0, 0.99),
-        TextLayout::new_with_justify(JustifyText::Left),
-        TextColor(UNSELECTED_COLOR),
-        OnPauseScreen,
-    )).id();
-    let quit_option_id = commands.spawn((
-        Name::new("QuitMenuOption"),
-        Text2d::new("Quit to Menu"),
-        text_font.clone(),
-        Transform::from_xyz(FRAME_BORDER_LEFT + 100.0, -60.0, 0.99),
-        TextLayout::new_with_justify(JustifyText::Left),
-        TextColor(UNSELECTED_COLOR),
+        Visibility::default(),
+        PauseMenuContent,
         OnPauseScreen,
-    )).id();
-    commands.insert_resource(PauseMenuState {
-        options: vec![continue_option_id, restart_option_id, quit_option_id],
-        selected: 0
-    })
+    ));
+}
+
+// a page asking before anything that throws away the run, with No selected to start with
+fn confirmation(label: &str, question: &str, action: PauseMenuAction) -> MenuItem<PauseMenuAction> {
+    MenuItem::submenu(label, vec![
+        MenuItem::back(question).enabled(false),
+        MenuItem::back("No"),
+        MenuItem::new("Yes", action),
+    ])
 }
-fn handle_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    game_state: ResMut<NextState<GameState>>,
-    mut menu_state: ResMut<PauseMenuState>,
+
+fn handle_menu_events(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut menu_event_reader: EventReader<MenuEvent<PauseMenuAction>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut chosen_level: ResMut<ChosenLevel>,
+    mut options_menu_return: ResMut<OptionsMenuReturn>,
+    first_level_state: Res<State<FirstLevelState>>,
+    rumia_state: Res<State<RumiaState>>,
+    practice: Option<Res<PracticeSession>>,
+    content_query: Query<Entity, With<PauseMenuContent>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
-        menu_state.selected = if menu_state.selected == 0 { menu_state.options.len() - 1 } else { menu_state.selected - 1 };
-    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
-        menu_state.selected = if menu_state.selected == menu_state.options.len() - 1 { 0 } else { menu_state.selected + 1 };
-    } else if keyboard_input.pressed(KeyCode::KeyZ) {
-        run_menu_action(menu_state.selected, game_state);
+    for menu_event in menu_event_reader.read() {
+        let MenuEvent::Confirm(action) = menu_event else {
+            continue;
+        };
+        match action {
+            PauseMenuAction::Resume => {
+                for entity in content_query.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                commands.spawn((
+                    Name::new("ResumeCountdownText"),
+                    Text2d::new(COUNTDOWN_STEPS.to_string()),
+                    TextFont {
+                        font: asset_server.load("fonts/Super-Cartoon.ttf"),
+                        font_size: 60.0,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Transform::from_xyz(-128.0, 4.0, 0.99),
+                    TextColor(UNSELECTED_COLOR),
+                    ResumeCountdown(Timer::new(COUNTDOWN_STEP * COUNTDOWN_STEPS, TimerMode::Once)),
+                    OnPauseScreen,
+                ));
+            },
+            PauseMenuAction::RetrySection => {
+                chosen_level.checkpoint = current_checkpoint(*first_level_state.get(), *rumia_state.get());
+                game_state.set(GameState::Resetting);
+            },
+            // a practice run restarts from the section that was picked, a full run from the very start
+            PauseMenuAction::Restart => {
+                chosen_level.checkpoint = practice.as_ref().map_or(Checkpoint::Start, |practice| practice.section().checkpoint);
+                game_state.set(GameState::Resetting);
+            },
+            PauseMenuAction::Options => {
+                options_menu_return.0 = GameState::Paused;
+                game_state.set(GameState::OptionsMenu);
+            },
+            PauseMenuAction::Quit => game_state.set(GameState::MainMenu),
+        }
     }
 }
 
-fn run_menu_action(
-    menu_selected: usize,
+fn count_down_to_resume(
+    time: Res<Time<Real>>,
+    mut countdown_query: Query<(&mut ResumeCountdown, &mut Text2d)>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
 ) {
-    match menu_selected {
-        0 => {
+    for (mut countdown, mut text) in countdown_query.iter_mut() {
+        if countdown.0.tick(time.delta()).finished() {
             game_state.set(GameState::PlayingGame);
-        },
-        1 => {
-            game_state.set(GameState::Resetting);
-        },
-        2 => {
-            game_state.set(GameState::MainMenu);
-        },
-        _ => {}
+            continue;
+        }
+        let step = (countdown.0.remaining_secs() / COUNTDOWN_STEP.as_secs_f32()).ceil() as u32;
+        if text.0 != step.to_string() {
+            text.0 = step.to_string();
+            play_sound_event_writer.send(PlaySoundEvent(SoundEffect::MenuSelect));
+        }
     }
 }
-fn draw(
-    menu_state: Res<PauseMenuState>,
-    mut text2d_query: Query<(Entity, &mut TextColor)>,
+
+// tagged as part of the run so it goes with everything else if the run ends
+fn spawn_options_backdrop(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    for text_option in text2d_query.iter_mut() {
-        let text2d = text_option.0;
-        let mut text_color = text_option.1;
-        if text2d == menu_state.options[menu_state.selected] {
-            text_color.0 = SELECTED_COLOR;
-        } else {
-            text_color.0 = UNSELECTED_COLOR;
-        }
-    }
+    commands.spawn((
+        Name::new("Pause Options Backdrop"),
+        Mesh2d(meshes.add(Rectangle::new(DEFAULT_RESOLUTION.x, DEFAULT_RESOLUTION.y))),
+        MeshMaterial2d(materials.add(Color::srgba(0.0, 0.0, 0.0, 0.9))),
+        Transform::from_xyz(0.0, 0.0, 99.0),
+        PauseOptionsBackdrop,
+        GameObject,
+    ));
 }
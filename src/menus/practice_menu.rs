@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use crate::{despawn_screen, GameState};
+use crate::game::{ChosenLevel, Difficulty};
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::UNSELECTED_COLOR;
+use crate::practice::{start_practice, PracticeRecords, PracticeSettings, PRACTICE_SECTIONS};
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, PartialEq)]
+enum PracticeMenuAction {
+    Section(usize),
+    Lives,
+    Bombs,
+    Power,
+    Back,
+}
+
+#[derive(Component)]
+struct OnPracticeMenuScreen;
+
+pub fn practice_menu_plugin(app: &mut App) {
+    app
+        .add_plugins(menu_plugin::<PracticeMenuAction>)
+        .add_systems(OnEnter(GameState::PracticeMenu), practice_menu_setup)
+        .add_systems(Update, (handle_menu_events, draw_settings).chain().run_if(in_state(GameState::PracticeMenu)))
+        .add_systems(OnExit(GameState::PracticeMenu), despawn_screen::<OnPracticeMenuScreen>)
+    ;
+}
+
+fn practice_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+) {
+    let font = asset_server.load("fonts/Super-Cartoon.ttf");
+
+    commands.spawn((
+        Name::new("PracticeTitleText"),
+        Text2d::new(format!("Practice - {}", difficulty.name())),
+        TextFont {
+            font,
+            font_size: 50.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 220.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        OnPracticeMenuScreen,
+    ));
+
+    // stages have to be reached in a normal run first, but every spell card can be practiced
+    let records = PracticeRecords::load();
+    let mut items: Vec<MenuItem<PracticeMenuAction>> = PRACTICE_SECTIONS.iter().enumerate()
+        .filter(|(_, section)| section.is_spell() || records.is_unlocked(section.level))
+        .map(|(i, section)| {
+            let record = records.sections.get(&section.record_key(*difficulty)).copied().unwrap_or_default();
+            let label = format!("{}   Best {}   Capture {}/{}", section.name, record.best_score, record.captures, record.attempts);
+            MenuItem::new(label, PracticeMenuAction::Section(i))
+        })
+        .collect();
+    // the settings labels are filled in by draw_settings
+    items.extend([
+        MenuItem::new("", PracticeMenuAction::Lives).adjustable(),
+        MenuItem::new("", PracticeMenuAction::Bombs).adjustable(),
+        MenuItem::new("", PracticeMenuAction::Power).adjustable(),
+        MenuItem::new("Back", PracticeMenuAction::Back),
+    ]);
+
+    commands.spawn((
+        Name::new("PracticeMenu"),
+        Menu::new(items, MenuStyle {
+            font_size: 25.0,
+            line_height: 35.0,
+            justify: JustifyText::Center,
+        }).with_cancel(PracticeMenuAction::Back),
+        Transform::from_xyz(0.0, 150.0, 100.0),
+        Visibility::default(),
+        OnPracticeMenuScreen,
+    ));
+}
+
+fn handle_menu_events(
+    mut commands: Commands,
+    mut menu_event_reader: EventReader<MenuEvent<PracticeMenuAction>>,
+    mut settings: ResMut<PracticeSettings>,
+    mut saved_settings: ResMut<Settings>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut chosen_level: ResMut<ChosenLevel>,
+) {
+    for menu_event in menu_event_reader.read() {
+        match menu_event {
+            MenuEvent::Adjust(PracticeMenuAction::Lives, step) => saved_settings.change_practice_lives(*step),
+            MenuEvent::Adjust(PracticeMenuAction::Bombs, step) => settings.change_bombs(*step),
+            MenuEvent::Adjust(PracticeMenuAction::Power, step) => settings.change_power(*step),
+            MenuEvent::Confirm(PracticeMenuAction::Section(section)) => {
+                start_practice(&mut commands, *section, &settings, saved_settings.practice_lives, &mut chosen_level);
+                game_state.set(GameState::StartingGame);
+            },
+            MenuEvent::Confirm(PracticeMenuAction::Back) => game_state.set(GameState::MainMenu),
+            _ => {},
+        }
+    }
+}
+
+fn draw_settings(
+    settings: Res<PracticeSettings>,
+    saved_settings: Res<Settings>,
+    mut menu_query: Query<&mut Menu<PracticeMenuAction>>,
+) {
+    for mut menu in menu_query.iter_mut() {
+        if !menu.is_added() && !settings.is_changed() && !saved_settings.is_changed() {
+            continue;
+        }
+        let labels = [
+            (PracticeMenuAction::Lives, format!("Lives  < {} >", saved_settings.practice_lives)),
+            (PracticeMenuAction::Bombs, format!("Bombs  < {} >", settings.bombs)),
+            (PracticeMenuAction::Power, format!("Power  < {} >", settings.power)),
+        ];
+        for (row, label) in labels {
+            if let Some(item) = menu.item_mut(|action| *action == row) {
+                item.label = label;
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use bevy::prelude::*;
+use crate::{despawn_screen, GameState};
+use crate::game::ChosenLevel;
+use crate::menus::menu::{menu_plugin, Menu, MenuEvent, MenuItem, MenuStyle};
+use crate::menus::UNSELECTED_COLOR;
+use crate::replay::{list_replays, start_replay_playback, Replay};
+use crate::rng::ChosenSeed;
+
+const MAX_LISTED_REPLAYS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ReplayMenuAction {
+    Watch(usize),
+    Back,
+}
+
+#[derive(Resource)]
+struct ReplayMenuState {
+    replays: Vec<PathBuf>,
+}
+
+#[derive(Component)]
+struct OnReplayMenuScreen;
+
+pub fn replay_menu_plugin(app: &mut App) {
+    app
+        .add_plugins(menu_plugin::<ReplayMenuAction>)
+        .add_systems(OnEnter(GameState::ReplayMenu), replay_menu_setup)
+        .add_systems(Update, handle_menu_events.run_if(in_state(GameState::ReplayMenu)))
+        .add_systems(OnExit(GameState::ReplayMenu), despawn_screen::<OnReplayMenuScreen>)
+    ;
+}
+
+fn replay_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/Super-Cartoon.ttf");
+
+    commands.spawn((
+        Name::new("ReplaysTitleText"),
+        Text2d::new("Replays"),
+        TextFont {
+            font,
+            font_size: 50.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 220.0, 100.0),
+        TextColor(UNSELECTED_COLOR),
+        OnReplayMenuScreen,
+    ));
+
+    let mut replays = list_replays();
+    replays.truncate(MAX_LISTED_REPLAYS);
+    let mut items: Vec<MenuItem<ReplayMenuAction>> = replays.iter().enumerate()
+        .map(|(i, path)| {
+            let label = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+            MenuItem::new(label, ReplayMenuAction::Watch(i))
+        })
+        .collect();
+    items.push(MenuItem::new("Back", ReplayMenuAction::Back));
+
+    commands.spawn((
+        Name::new("ReplayMenu"),
+        Menu::new(items, MenuStyle {
+            font_size: 30.0,
+            line_height: 35.0,
+            justify: JustifyText::Center,
+        }).with_cancel(ReplayMenuAction::Back),
+        Transform::from_xyz(0.0, 150.0, 100.0),
+        Visibility::default(),
+        OnReplayMenuScreen,
+    ));
+    commands.insert_resource(ReplayMenuState {
+        replays,
+    });
+}
+
+fn handle_menu_events(
+    mut commands: Commands,
+    mut menu_event_reader: EventReader<MenuEvent<ReplayMenuAction>>,
+    menu_state: Res<ReplayMenuState>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut chosen_seed: ResMut<ChosenSeed>,
+    mut chosen_level: ResMut<ChosenLevel>,
+) {
+    for menu_event in menu_event_reader.read() {
+        match menu_event {
+            MenuEvent::Confirm(ReplayMenuAction::Watch(i)) => {
+                let path = &menu_state.replays[*i];
+                match Replay::load(path) {
+                    Ok(replay) => {
+                        start_replay_playback(&mut commands, replay, &mut chosen_seed, &mut chosen_level);
+                        game_state.set(GameState::StartingGame);
+                    },
+                    Err(error) => error!("Could not load replay {}: {}", path.display(), error),
+                }
+            },
+            MenuEvent::Confirm(ReplayMenuAction::Back) => game_state.set(GameState::MainMenu),
+            MenuEvent::Adjust(..) => {},
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::f32::consts::PI;
 use crate::movement_patterns;
 use crate::movement_patterns::{MovementPattern, MovementPatterns};
-use bevy::math::{Rot2, Vec2, Vec3};
+use bevy::math::{Rot2, Vec3};
 use bevy::prelude::{Res, Time, Transform};
 use std::time::Duration;
 use crate::movement_patterns::MovementPatterns::DeceleratePattern;
@@ -69,18 +69,3 @@ pub fn create_decelerate_pattern(angle: Rot2, starting_speed: f32, final_speed:
         }
     )
 }
-
-pub fn create_move_to_pattern(starting_position: Vec2, destination: Vec2, time: Duration) -> MovementPatterns {
-    let displacement = destination - starting_position;
-    let angle = displacement.y.atan2(displacement.x);
-    let distance = displacement.length();
-    let current_speed = distance * 2.0 / time.as_secs_f32();
-    DeceleratePattern(
-        Decelerate {
-            angle: Rot2::radians(angle),
-            current_speed,
-            final_speed: 0.0,
-            deceleration: -current_speed / time.as_secs_f32(),
-        }
-    )
-}
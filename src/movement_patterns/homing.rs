@@ -0,0 +1,73 @@
+use std::f32::consts::PI;
+use bevy::math::{Rot2, Vec2, Vec3};
+use bevy::prelude::{Res, Time, Timer, TimerMode, Transform};
+use crate::movement_patterns;
+use crate::movement_patterns::{MovementPattern, MovementPatterns};
+use crate::movement_patterns::MovementPatterns::HomingPattern;
+
+#[derive(Clone, PartialEq)]
+pub struct Homing {
+    pub angle: Rot2,
+    pub speed: f32,
+    pub turn_rate: f32,
+    pub target: Option<Vec2>,
+    pub lifetime: Option<Timer>,
+}
+
+impl Homing {
+    fn is_homing(&self) -> bool {
+        self.lifetime.as_ref().is_none_or(|lifetime| !lifetime.finished())
+    }
+}
+
+impl MovementPattern for Homing {
+    fn name(&self) -> &str {
+        "Homing"
+    }
+
+    fn do_move(&mut self, transform: &mut Transform, time: &Res<Time>, face_travel: bool) {
+        if let Some(lifetime) = &mut self.lifetime {
+            lifetime.tick(time.delta());
+        }
+        if let Some(target) = self.target.filter(|_| self.is_homing()) {
+            let difference = target - transform.translation.truncate();
+            if difference != Vec2::ZERO {
+                let desired_angle = difference.y.atan2(difference.x);
+                let mut angle_delta = desired_angle - self.angle.as_radians();
+                if angle_delta > PI {
+                    angle_delta -= 2.0 * PI;
+                } else if angle_delta < -PI {
+                    angle_delta += 2.0 * PI;
+                }
+                let max_turn = self.turn_rate * time.delta_secs();
+                self.angle = Rot2::radians(self.angle.as_radians() + angle_delta.clamp(-max_turn, max_turn));
+            }
+        }
+
+        let movement_direction = Vec3::new(self.angle.cos, self.angle.sin, 0.0);
+        transform.translation += movement_direction * self.speed * time.delta_secs();
+        if face_travel {
+            movement_patterns::face_travel_direction(transform, movement_direction);
+        }
+    }
+
+    fn lateral_movement(&self) -> f32 {
+        self.angle.as_radians()
+    }
+
+    fn is_finished(&self) -> bool {
+        false
+    }
+}
+
+pub fn create_homing_pattern(angle: Rot2, speed: f32, turn_rate: f32, lifetime_secs: Option<f32>) -> MovementPatterns {
+    HomingPattern(
+        Homing {
+            angle,
+            speed,
+            turn_rate,
+            target: None,
+            lifetime: lifetime_secs.map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+        }
+    )
+}
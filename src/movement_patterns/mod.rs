@@ -2,15 +2,24 @@ pub mod sine_wave;
 pub mod decelerate;
 pub mod straight_line;
 pub mod curved_line;
+pub mod homing;
+pub mod easing;
+pub mod path;
+pub mod sequence;
+pub mod move_to;
 
 use crate::movement_patterns::decelerate::Decelerate;
 use crate::movement_patterns::sine_wave::SineWave;
 use crate::movement_patterns::straight_line::StraightLine;
-use crate::movement_patterns::MovementPatterns::{DeceleratePattern, DontMovePattern, SineWavePattern, StraightLinePattern, CurvedLinePattern};
-use bevy::math::{Quat, Vec3};
+use crate::movement_patterns::MovementPatterns::{DeceleratePattern, DontMovePattern, SineWavePattern, StraightLinePattern, CurvedLinePattern, HomingPattern, PathPattern, SequencePattern, MoveToPattern};
+use bevy::math::{Quat, Vec2, Vec3};
 use bevy::prelude::{Component, Res, Time, Transform};
 use std::f32::consts::PI;
 use crate::movement_patterns::curved_line::CurvedLine;
+use crate::movement_patterns::homing::Homing;
+use crate::movement_patterns::path::Path;
+use crate::movement_patterns::sequence::Sequence;
+use crate::movement_patterns::move_to::MoveTo;
 
 #[derive(Component, Clone, PartialEq)]
 pub enum MovementPatterns {
@@ -18,7 +27,11 @@ pub enum MovementPatterns {
     StraightLinePattern(StraightLine),
     DeceleratePattern(Decelerate),
     SineWavePattern(SineWave),
-    CurvedLinePattern(CurvedLine)
+    CurvedLinePattern(CurvedLine),
+    HomingPattern(Homing),
+    PathPattern(Path),
+    SequencePattern(Sequence),
+    MoveToPattern(MoveTo),
 }
 
 pub fn run_movement_pattern(movement_pattern: &mut MovementPatterns, transform: &mut Transform, time: &Res<Time>, face_travel_direction: bool) {
@@ -35,6 +48,18 @@ pub fn run_movement_pattern(movement_pattern: &mut MovementPatterns, transform:
         CurvedLinePattern(curved_line) => {
             curved_line.do_move(transform, time, face_travel_direction)
         }
+        HomingPattern(homing) => {
+            homing.do_move(transform, time, face_travel_direction)
+        }
+        PathPattern(path) => {
+            path.do_move(transform, time, face_travel_direction)
+        }
+        SequencePattern(sequence) => {
+            sequence.do_move(transform, time, face_travel_direction)
+        }
+        MoveToPattern(move_to) => {
+            move_to.do_move(transform, time, face_travel_direction)
+        }
         DontMovePattern(_dont_move) => {}
     }
 }
@@ -46,6 +71,10 @@ pub fn get_lateral_movement(movement_pattern: &MovementPatterns) -> f32 {
         DeceleratePattern(pattern) => { pattern.lateral_movement() }
         SineWavePattern(pattern) => { pattern.lateral_movement() }
         CurvedLinePattern(pattern) => { pattern.lateral_movement() }
+        HomingPattern(pattern) => { pattern.lateral_movement() }
+        PathPattern(pattern) => { pattern.lateral_movement() }
+        SequencePattern(pattern) => { pattern.lateral_movement() }
+        MoveToPattern(pattern) => { pattern.lateral_movement() }
     }
 }
 
@@ -56,6 +85,23 @@ pub fn is_finished(movement_pattern: &MovementPatterns) -> bool {
         DeceleratePattern(pattern) => { pattern.is_finished() }
         SineWavePattern(pattern) => { pattern.is_finished() }
         CurvedLinePattern(pattern) => { pattern.is_finished() }
+        HomingPattern(pattern) => { pattern.is_finished() }
+        PathPattern(pattern) => { pattern.is_finished() }
+        SequencePattern(pattern) => { pattern.is_finished() }
+        MoveToPattern(pattern) => { pattern.is_finished() }
+    }
+}
+
+pub fn get_movement_step(movement_pattern: &MovementPatterns) -> Option<usize> {
+    match movement_pattern {
+        SequencePattern(sequence) => Some(sequence.current_step()),
+        _ => None,
+    }
+}
+
+pub fn set_homing_target(movement_pattern: &mut MovementPatterns, target: Option<Vec2>) {
+    if let HomingPattern(homing) = movement_pattern {
+        homing.target = target;
     }
 }
 
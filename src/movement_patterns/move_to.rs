@@ -0,0 +1,63 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::{Res, Time, Timer, TimerMode, Transform};
+use crate::movement_patterns;
+use crate::movement_patterns::easing::Easing;
+use crate::movement_patterns::{MovementPattern, MovementPatterns};
+use crate::movement_patterns::MovementPatterns::MoveToPattern;
+
+// moves from wherever the entity is on its first move and lands exactly on the destination
+#[derive(Clone, PartialEq)]
+pub struct MoveTo {
+    pub destination: Vec2,
+    pub timer: Timer,
+    pub easing: Easing,
+    start: Option<Vec2>,
+}
+
+impl MovementPattern for MoveTo {
+    fn name(&self) -> &str {
+        "MoveTo"
+    }
+
+    fn do_move(&mut self, transform: &mut Transform, time: &Res<Time>, face_travel: bool) {
+        let start = *self.start.get_or_insert(transform.translation.truncate());
+        self.timer.tick(time.delta());
+        let position = if self.timer.finished() {
+            self.destination
+        } else {
+            start.lerp(self.destination, self.easing.apply(self.timer.fraction()))
+        };
+        transform.translation = position.extend(transform.translation.z);
+        if face_travel {
+            let direction = self.destination - start;
+            movement_patterns::face_travel_direction(transform, Vec3::new(direction.x, direction.y, 0.0));
+        }
+    }
+
+    fn lateral_movement(&self) -> f32 {
+        match self.start {
+            Some(start) if !self.is_finished() && start != self.destination => {
+                let direction = self.destination - start;
+                direction.y.atan2(direction.x)
+            }
+            _ => PI / 2.0,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.timer.finished()
+    }
+}
+
+pub fn create_move_to_pattern(destination: Vec2, time: Duration, easing: Easing) -> MovementPatterns {
+    MoveToPattern(
+        MoveTo {
+            destination,
+            timer: Timer::new(time, TimerMode::Once),
+            easing,
+            start: None,
+        }
+    )
+}
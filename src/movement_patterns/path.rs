@@ -0,0 +1,214 @@
+use std::f32::consts::PI;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::{Res, Time, Transform};
+use crate::movement_patterns;
+use crate::movement_patterns::easing::Easing;
+use crate::movement_patterns::{MovementPattern, MovementPatterns};
+use crate::movement_patterns::MovementPatterns::PathPattern;
+
+const BEZIER_LENGTH_SAMPLES: usize = 16;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PathShape {
+    Line(Vec2),
+    CubicBezier(Vec2, Vec2, Vec2),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct PathSegment {
+    pub shape: PathShape,
+    pub speed: f32,
+    pub easing: Easing,
+    pub pause_secs: f32,
+}
+
+impl PathSegment {
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_pause(mut self, pause_secs: f32) -> Self {
+        self.pause_secs = pause_secs;
+        self
+    }
+
+    fn end(&self) -> Vec2 {
+        match self.shape {
+            PathShape::Line(end) => end,
+            PathShape::CubicBezier(_, _, end) => end,
+        }
+    }
+
+    fn point_at(&self, start: Vec2, t: f32) -> Vec2 {
+        match self.shape {
+            PathShape::Line(end) => start.lerp(end, t),
+            PathShape::CubicBezier(control1, control2, end) => {
+                let u = 1.0 - t;
+                start * u * u * u
+                    + control1 * 3.0 * u * u * t
+                    + control2 * 3.0 * u * t * t
+                    + end * t * t * t
+            }
+        }
+    }
+
+    fn length(&self, start: Vec2) -> f32 {
+        match self.shape {
+            PathShape::Line(end) => start.distance(end),
+            PathShape::CubicBezier(..) => {
+                (1..=BEZIER_LENGTH_SAMPLES)
+                    .map(|i| {
+                        let previous = self.point_at(start, (i - 1) as f32 / BEZIER_LENGTH_SAMPLES as f32);
+                        let next = self.point_at(start, i as f32 / BEZIER_LENGTH_SAMPLES as f32);
+                        previous.distance(next)
+                    })
+                    .sum()
+            }
+        }
+    }
+
+    fn duration(&self, start: Vec2) -> f32 {
+        if self.speed <= 0.0 {
+            0.0
+        } else {
+            self.length(start) / self.speed
+        }
+    }
+}
+
+pub fn line_to(point: Vec2, speed: f32) -> PathSegment {
+    PathSegment {
+        shape: PathShape::Line(point),
+        speed,
+        easing: Easing::Linear,
+        pause_secs: 0.0,
+    }
+}
+
+pub fn bezier_to(control1: Vec2, control2: Vec2, end: Vec2, speed: f32) -> PathSegment {
+    PathSegment {
+        shape: PathShape::CubicBezier(control1, control2, end),
+        speed,
+        easing: Easing::Linear,
+        pause_secs: 0.0,
+    }
+}
+
+// follows its segments in order starting from wherever the entity is on its first move
+#[derive(Clone, PartialEq)]
+pub struct Path {
+    pub segments: Vec<PathSegment>,
+    current_segment: usize,
+    segment_start: Option<Vec2>,
+    elapsed: f32,
+    lateral_movement: f32,
+}
+
+impl MovementPattern for Path {
+    fn name(&self) -> &str {
+        "Path"
+    }
+
+    fn do_move(&mut self, transform: &mut Transform, time: &Res<Time>, face_travel: bool) {
+        let old_position = transform.translation.truncate();
+        let mut segment_start = *self.segment_start.get_or_insert(old_position);
+        let mut position = old_position;
+        let mut remaining = time.delta_secs();
+        while remaining > 0.0 && !self.is_finished() {
+            let segment = &self.segments[self.current_segment];
+            let duration = segment.duration(segment_start);
+            if self.elapsed < duration {
+                let step = remaining.min(duration - self.elapsed);
+                self.elapsed += step;
+                remaining -= step;
+                position = segment.point_at(segment_start, segment.easing.apply(self.elapsed / duration));
+            } else if self.elapsed < duration + segment.pause_secs {
+                let step = remaining.min(duration + segment.pause_secs - self.elapsed);
+                self.elapsed += step;
+                remaining -= step;
+                position = segment.end();
+            } else {
+                position = segment.end();
+                segment_start = position;
+                self.segment_start = Some(position);
+                self.current_segment += 1;
+                self.elapsed = 0.0;
+            }
+        }
+
+        let direction = position - old_position;
+        self.lateral_movement = if direction.length_squared() > f32::EPSILON {
+            direction.y.atan2(direction.x)
+        } else {
+            PI / 2.0
+        };
+        transform.translation = position.extend(transform.translation.z);
+        if face_travel && direction != Vec2::ZERO {
+            movement_patterns::face_travel_direction(transform, Vec3::new(direction.x, direction.y, 0.0));
+        }
+    }
+
+    fn lateral_movement(&self) -> f32 {
+        self.lateral_movement
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current_segment >= self.segments.len()
+    }
+}
+
+pub fn create_path_pattern(segments: Vec<PathSegment>) -> MovementPatterns {
+    PathPattern(
+        Path {
+            segments,
+            current_segment: 0,
+            segment_start: None,
+            elapsed: 0.0,
+            lateral_movement: PI / 2.0,
+        }
+    )
+}
+
+pub fn create_waypoint_pattern(waypoints: &[Vec2], speed: f32) -> MovementPatterns {
+    create_path_pattern(waypoints.iter().map(|waypoint| line_to(*waypoint, speed)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use super::*;
+    use crate::game::UP_DOWN_MOVEMENT_BRACKET;
+    use crate::movement_patterns::{get_lateral_movement, run_movement_pattern};
+
+    #[test]
+    fn lateral_movement_follows_the_direction_of_the_current_segment() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(100));
+        world.insert_resource(time);
+
+        // a second to the right, then a second back to the left
+        let lateral_movements = world.run_system_once(|time: Res<Time>| {
+            let mut movement_pattern = create_waypoint_pattern(&[Vec2::new(100.0, 10.0), Vec2::new(0.0, 20.0)], 100.0);
+            let mut transform = Transform::default();
+            (0..20)
+                .map(|_| {
+                    run_movement_pattern(&mut movement_pattern, &mut transform, &time, false);
+                    get_lateral_movement(&movement_pattern)
+                })
+                .collect::<Vec<f32>>()
+        }).unwrap();
+
+        for lateral_movement in &lateral_movements[..9] {
+            assert!(lateral_movement.cos() > 0.0, "{}", lateral_movement);
+            assert!(lateral_movement.abs() < UP_DOWN_MOVEMENT_BRACKET.start, "{}", lateral_movement);
+        }
+        for lateral_movement in &lateral_movements[11..19] {
+            assert!(lateral_movement.cos() < 0.0, "{}", lateral_movement);
+            assert!(lateral_movement.abs() >= UP_DOWN_MOVEMENT_BRACKET.end, "{}", lateral_movement);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::{Res, Time, Timer, TimerMode, Transform};
+use crate::movement_patterns::{get_lateral_movement, is_finished, run_movement_pattern, MovementPattern, MovementPatterns};
+use crate::movement_patterns::MovementPatterns::SequencePattern;
+
+#[derive(Clone, PartialEq)]
+pub struct SequenceStep {
+    pub movement_pattern: MovementPatterns,
+    pub duration: Option<Timer>,
+}
+
+impl SequenceStep {
+    fn is_complete(&self) -> bool {
+        match &self.duration {
+            Some(duration) => duration.finished(),
+            None => is_finished(&self.movement_pattern),
+        }
+    }
+}
+
+// runs each step until its child pattern finishes, or for its duration if it has one
+#[derive(Clone, PartialEq)]
+pub struct Sequence {
+    pub steps: Vec<SequenceStep>,
+    pub looping: bool,
+    current_step: usize,
+    active_step: Box<SequenceStep>,
+    finished: bool,
+}
+
+impl Sequence {
+    pub fn current_step(&self) -> usize {
+        self.current_step
+    }
+
+    fn advance(&mut self) {
+        if self.current_step + 1 < self.steps.len() {
+            self.current_step += 1;
+        } else if self.looping {
+            self.current_step = 0;
+        } else {
+            self.finished = true;
+            return;
+        }
+        *self.active_step = self.steps[self.current_step].clone();
+    }
+}
+
+impl MovementPattern for Sequence {
+    fn name(&self) -> &str {
+        "Sequence"
+    }
+
+    fn do_move(&mut self, transform: &mut Transform, time: &Res<Time>, face_travel: bool) {
+        if self.finished {
+            return;
+        }
+        if let Some(duration) = &mut self.active_step.duration {
+            duration.tick(time.delta());
+        }
+        run_movement_pattern(&mut self.active_step.movement_pattern, transform, time, face_travel);
+        if self.active_step.is_complete() {
+            self.advance();
+        }
+    }
+
+    fn lateral_movement(&self) -> f32 {
+        get_lateral_movement(&self.active_step.movement_pattern)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+pub fn step(movement_pattern: MovementPatterns) -> SequenceStep {
+    SequenceStep {
+        movement_pattern,
+        duration: None,
+    }
+}
+
+pub fn timed_step(movement_pattern: MovementPatterns, duration_secs: f32) -> SequenceStep {
+    SequenceStep {
+        movement_pattern,
+        duration: Some(Timer::from_seconds(duration_secs, TimerMode::Once)),
+    }
+}
+
+pub fn create_sequence_pattern(steps: Vec<SequenceStep>, looping: bool) -> MovementPatterns {
+    let active_step = Box::new(steps.first().cloned().expect("a sequence needs at least one step"));
+    SequencePattern(
+        Sequence {
+            steps,
+            looping,
+            current_step: 0,
+            active_step,
+            finished: false,
+        }
+    )
+}
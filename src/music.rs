@@ -0,0 +1,601 @@
+use std::fs;
+use std::io;
+use std::time::Duration;
+use bevy::audio::{AudioPlayer, AudioSink, AudioSinkPlayback, AudioSource, Decodable, PlaybackSettings, Source, Volume};
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use crate::game::{FRAME_BORDER_BOTTOM, FRAME_BORDER_LEFT};
+use crate::level1::FirstLevelState;
+use crate::replay::ReplayPlayback;
+use crate::settings::{data_path, save_data_file, Settings};
+use crate::GameState;
+
+pub const MUSIC_UNLOCKS_FILE_NAME: &str = "music.txt";
+const CROSSFADE: Duration = Duration::from_millis(1500);
+const NOW_PLAYING_DURATION: Duration = Duration::from_secs(4);
+const NOW_PLAYING_FADE: Duration = Duration::from_secs(1);
+const NOW_PLAYING_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MusicTrack {
+    Title,
+    Stage1,
+    Rumia,
+}
+
+pub struct TrackInfo {
+    pub title: &'static str,
+    // how the track is named in the unlocks file
+    pub key: &'static str,
+    pub comment: &'static str,
+    pub path: &'static str,
+    // the track plays through once, then repeats the part between these forever
+    pub loop_start: Duration,
+    // the end of the file if not set
+    pub loop_end: Option<Duration>,
+}
+
+static TRACKS: [TrackInfo; 3] = [
+    TrackInfo {
+        title: "A Dream More Scarlet than Red",
+        key: "title",
+        comment: "The title screen theme.\nA quiet arpeggio that opens\ninto the melody the whole game returns to.",
+        path: "bgm/title.wav",
+        loop_start: Duration::from_millis(4_800),
+        loop_end: Some(Duration::from_millis(24_000)),
+    },
+    TrackInfo {
+        title: "A Soul as Red as a Ground Cherry",
+        key: "stage1",
+        comment: "The first stage theme.\nDusk over the lake, with a bright\nlead to ease you into the night.",
+        path: "bgm/stage1.wav",
+        loop_start: Duration::from_millis(3_840),
+        loop_end: Some(Duration::from_millis(19_200)),
+    },
+    TrackInfo {
+        title: "Apparitions Stalk the Night",
+        key: "rumia",
+        comment: "Rumia's theme.\nShe isn't much of a threat, so the\ntune stays playful rather than menacing.",
+        path: "bgm/rumia.wav",
+        loop_start: Duration::from_millis(1_600),
+        loop_end: Some(Duration::from_millis(14_400)),
+    },
+];
+
+impl MusicTrack {
+    pub const ALL: [MusicTrack; 3] = [MusicTrack::Title, MusicTrack::Stage1, MusicTrack::Rumia];
+
+    pub fn info(self) -> &'static TrackInfo {
+        &TRACKS[self as usize]
+    }
+}
+
+impl TrackInfo {
+    // where playback is after this long, counting the repeats of the loop
+    pub fn position(&self, elapsed: Duration) -> Duration {
+        match self.loop_end {
+            Some(loop_end) if elapsed >= loop_end && loop_end > self.loop_start => {
+                let loop_length = (loop_end - self.loop_start).as_nanos();
+                let into_loop = (elapsed - self.loop_start).as_nanos() % loop_length;
+                self.loop_start + Duration::from_nanos(into_loop as u64)
+            },
+            _ => elapsed,
+        }
+    }
+}
+
+// the tracks heard in play so far, which the music room lets you listen to
+#[derive(Default, Debug, PartialEq)]
+pub struct MusicUnlocks {
+    pub unlocked: Vec<MusicTrack>,
+}
+
+impl MusicUnlocks {
+    pub fn load() -> Self {
+        match fs::read_to_string(data_path(MUSIC_UNLOCKS_FILE_NAME)) {
+            Ok(text) => Self::decode(&text).unwrap_or_else(|error| {
+                error!("Could not read music unlocks: {}", error);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        save_data_file(MUSIC_UNLOCKS_FILE_NAME, self.encode())
+    }
+
+    pub fn encode(&self) -> String {
+        self.unlocked.iter()
+            .map(|track| format!("unlocked {}", track.info().key))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn decode(text: &str) -> io::Result<Self> {
+        let mut unlocks = Self::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let ["unlocked", key] = fields.as_slice() else {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unexpected line {}", line)));
+            };
+            let track = MusicTrack::ALL.into_iter().find(|track| track.info().key == *key)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown track {}", key)))?;
+            unlocks.unlock(track);
+        }
+        Ok(unlocks)
+    }
+
+    // the title theme plays before anything else, so it never needs unlocking
+    pub fn is_unlocked(&self, track: MusicTrack) -> bool {
+        track == MusicTrack::Title || self.unlocked.contains(&track)
+    }
+
+    // returns whether the track was newly unlocked
+    pub fn unlock(&mut self, track: MusicTrack) -> bool {
+        if self.is_unlocked(track) {
+            return false;
+        }
+        self.unlocked.push(track);
+        true
+    }
+}
+
+// switches the music, crossfading from whatever was playing. asking for the current track does nothing
+#[derive(Event)]
+pub struct PlayMusicEvent(pub MusicTrack);
+
+#[derive(Resource, Default)]
+pub struct CurrentMusic(pub Option<MusicTrack>);
+
+// set while the game is paused, including any options menus opened from the pause menu
+#[derive(Resource, Default)]
+pub struct MusicPaused(pub bool);
+
+// whether hearing a track saves it to the unlocks file, which the headless app turns off
+#[derive(Resource, PartialEq)]
+pub struct UnlockHeardMusic(pub bool);
+
+impl Default for UnlockHeardMusic {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// how far into the current track playback is, wrapped back into the loop
+#[derive(Resource, Default)]
+pub struct MusicPosition(pub Duration);
+
+// an audio file that repeats between its loop points instead of from the start
+#[derive(Asset, TypePath)]
+pub struct LoopingMusic {
+    source: AudioSource,
+    loop_start: Duration,
+    loop_end: Option<Duration>,
+}
+
+impl Decodable for LoopingMusic {
+    type DecoderItem = <AudioSource as Decodable>::DecoderItem;
+    type Decoder = LoopingDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        LoopingDecoder::new(self.source.decoder(), self.loop_start, self.loop_end)
+    }
+}
+
+// streams the file once, keeping the samples between the loop points, then plays those back forever
+pub struct LoopingDecoder {
+    file: <AudioSource as Decodable>::Decoder,
+    channels: u16,
+    sample_rate: u32,
+    position: usize,
+    loop_start: usize,
+    loop_end: usize,
+    body: Vec<<AudioSource as Decodable>::DecoderItem>,
+    replaying: Option<usize>,
+}
+
+impl LoopingDecoder {
+    fn new(file: <AudioSource as Decodable>::Decoder, loop_start: Duration, loop_end: Option<Duration>) -> Self {
+        let channels = file.channels();
+        let sample_rate = file.sample_rate();
+        let to_samples = |time: Duration| (time.as_secs_f64() * sample_rate as f64).round() as usize * channels as usize;
+        Self {
+            file,
+            channels,
+            sample_rate,
+            position: 0,
+            loop_start: to_samples(loop_start),
+            loop_end: loop_end.map_or(usize::MAX, to_samples),
+            body: Vec::new(),
+            replaying: None,
+        }
+    }
+}
+
+impl Iterator for LoopingDecoder {
+    type Item = <AudioSource as Decodable>::DecoderItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(index) = self.replaying.as_mut() {
+            let sample = *self.body.get(*index)?;
+            *index = (*index + 1) % self.body.len();
+            return Some(sample);
+        }
+        match self.file.next() {
+            Some(sample) if self.position < self.loop_end => {
+                if self.position >= self.loop_start {
+                    self.body.push(sample);
+                }
+                self.position += 1;
+                Some(sample)
+            },
+            // past the loop end or out of file, so the first pass is over
+            _ => {
+                self.replaying = Some(0);
+                self.next()
+            },
+        }
+    }
+}
+
+impl Source for LoopingDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// the time spent actually playing, which the sink doesn't report itself
+#[derive(Component)]
+struct Music {
+    track: MusicTrack,
+    elapsed: Duration,
+}
+
+// waiting on the file to load before it can start
+#[derive(Component)]
+struct PendingMusic(Handle<AudioSource>);
+
+#[derive(Component)]
+struct MusicFade {
+    timer: Timer,
+    fading_out: bool,
+}
+
+impl MusicFade {
+    fn new(fading_out: bool) -> Self {
+        Self {
+            timer: Timer::new(CROSSFADE, TimerMode::Once),
+            fading_out,
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        if self.fading_out {
+            self.timer.fraction_remaining()
+        } else {
+            self.timer.fraction()
+        }
+    }
+}
+
+#[derive(Component)]
+struct NowPlayingText(Timer);
+
+pub fn music_plugin(app: &mut App) {
+    app
+        .add_event::<PlayMusicEvent>()
+        .init_resource::<CurrentMusic>()
+        .init_resource::<MusicPosition>()
+        .init_resource::<UnlockHeardMusic>()
+        .init_resource::<MusicPaused>()
+        .add_systems(OnEnter(GameState::MainMenu), play_track(MusicTrack::Title))
+        .add_systems(OnEnter(GameState::PracticeMenu), play_track(MusicTrack::Title))
+        .add_systems(OnEnter(FirstLevelState::PreRumia), play_track(MusicTrack::Stage1))
+        .add_systems(OnEnter(FirstLevelState::Rumia), play_track(MusicTrack::Rumia))
+        .add_systems(OnEnter(FirstLevelState::PostRumia), play_track(MusicTrack::Stage1))
+        .add_systems(OnEnter(GameState::Paused), pause_music)
+        .add_systems(OnExit(GameState::Paused), resume_music)
+        .add_systems(Update, (
+            switch_music,
+            start_loaded_music,
+            fade_music,
+            track_music_position,
+            show_now_playing,
+        ).chain())
+        .add_systems(Update, unlock_heard_music
+            .run_if(resource_changed::<CurrentMusic>)
+            .run_if(resource_equals(UnlockHeardMusic(true)))
+            .run_if(not(in_state(GameState::MusicRoom)).and(not(resource_exists::<ReplayPlayback>))))
+    ;
+}
+
+fn play_track(track: MusicTrack) -> impl FnMut(EventWriter<PlayMusicEvent>) {
+    move |mut play_music_event_writer: EventWriter<PlayMusicEvent>| {
+        play_music_event_writer.send(PlayMusicEvent(track));
+    }
+}
+
+fn switch_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut play_music_event_reader: EventReader<PlayMusicEvent>,
+    mut current_music: ResMut<CurrentMusic>,
+    mut music_position: ResMut<MusicPosition>,
+    music_query: Query<Entity, (With<Music>, Without<MusicFade>)>,
+    mut fading_query: Query<&mut MusicFade, With<Music>>,
+) {
+    let Some(PlayMusicEvent(track)) = play_music_event_reader.read().last() else {
+        return;
+    };
+    if current_music.0 == Some(*track) {
+        return;
+    }
+    current_music.0 = Some(*track);
+    music_position.0 = Duration::ZERO;
+
+    for entity in music_query.iter() {
+        commands.entity(entity).insert(MusicFade::new(true));
+    }
+    // a track still fading in goes out from where it got to
+    for mut fade in fading_query.iter_mut().filter(|fade| !fade.fading_out) {
+        let elapsed = fade.timer.remaining();
+        *fade = MusicFade::new(true);
+        fade.timer.set_elapsed(elapsed.min(CROSSFADE));
+    }
+    commands.spawn((
+        Name::new("Music"),
+        Music {
+            track: *track,
+            elapsed: Duration::ZERO,
+        },
+        PendingMusic(asset_server.load(track.info().path)),
+        MusicFade::new(false),
+    ));
+}
+
+fn start_loaded_music(
+    mut commands: Commands,
+    audio_sources: Res<Assets<AudioSource>>,
+    mut looping_music: ResMut<Assets<LoopingMusic>>,
+    music_paused: Res<MusicPaused>,
+    pending_query: Query<(Entity, &Music, &PendingMusic)>,
+) {
+    for (entity, Music { track, .. }, PendingMusic(handle)) in pending_query.iter() {
+        let Some(source) = audio_sources.get(handle) else {
+            continue;
+        };
+        let info = track.info();
+        let music = looping_music.add(LoopingMusic {
+            source: source.clone(),
+            loop_start: info.loop_start,
+            loop_end: info.loop_end,
+        });
+        commands.entity(entity)
+            .remove::<PendingMusic>()
+            .insert((
+                AudioPlayer(music),
+                PlaybackSettings {
+                    volume: Volume::new(0.0),
+                    paused: music_paused.0,
+                    ..PlaybackSettings::ONCE
+                },
+            ));
+    }
+}
+
+// music has its own volume, scaled by any crossfade in progress
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    mut music_query: Query<(Entity, Option<&AudioSink>, Option<&mut MusicFade>), With<Music>>,
+) {
+    for (entity, sink, fade) in music_query.iter_mut() {
+        let mut volume = settings.bgm_volume();
+        if let Some(mut fade) = fade {
+            if fade.timer.tick(time.delta()).finished() {
+                if fade.fading_out {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+                commands.entity(entity).remove::<MusicFade>();
+            }
+            volume *= fade.volume();
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+fn track_music_position(
+    time: Res<Time<Real>>,
+    mut music_position: ResMut<MusicPosition>,
+    mut music_query: Query<(&mut Music, &AudioSink, Option<&MusicFade>)>,
+) {
+    for (mut music, sink, fade) in music_query.iter_mut() {
+        if sink.is_paused() {
+            continue;
+        }
+        music.elapsed += time.delta();
+        // a track on its way out isn't the one being listened to
+        if !fade.is_some_and(|fade| fade.fading_out) {
+            music_position.0 = music.track.info().position(music.elapsed);
+        }
+    }
+}
+
+fn unlock_heard_music(
+    current_music: Res<CurrentMusic>,
+) {
+    let Some(track) = current_music.0 else {
+        return;
+    };
+    let mut unlocks = MusicUnlocks::load();
+    if unlocks.unlock(track) {
+        if let Err(error) = unlocks.save() {
+            error!("Could not save music unlocks: {}", error);
+        }
+    }
+}
+
+fn pause_music(
+    mut music_paused: ResMut<MusicPaused>,
+    sink_query: Query<&AudioSink, With<Music>>,
+) {
+    music_paused.0 = true;
+    for sink in sink_query.iter() {
+        sink.pause();
+    }
+}
+
+// the options menus opened from pause always lead back to it, so the music waits until the game itself resumes
+fn resume_music(
+    game_state: Res<State<GameState>>,
+    mut music_paused: ResMut<MusicPaused>,
+    sink_query: Query<&AudioSink, With<Music>>,
+) {
+    if *game_state.get() == GameState::OptionsMenu {
+        return;
+    }
+    music_paused.0 = false;
+    for sink in sink_query.iter() {
+        sink.play();
+    }
+}
+
+fn show_now_playing(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time<Real>>,
+    current_music: Res<CurrentMusic>,
+    mut text_query: Query<(Entity, &mut NowPlayingText, &mut TextColor)>,
+) {
+    if current_music.is_changed() {
+        for (entity, _, _) in text_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        if let Some(track) = current_music.0 {
+            commands.spawn((
+                Name::new("NowPlayingText"),
+                Text2d::new(format!("BGM: {}", track.info().title)),
+                TextFont {
+                    font: asset_server.load("fonts/Super-Cartoon.ttf"),
+                    font_size: 16.0,
+                    ..default()
+                },
+                Anchor::BottomLeft,
+                Transform::from_xyz(FRAME_BORDER_LEFT + 8.0, FRAME_BORDER_BOTTOM + 8.0, 0.97),
+                TextColor(NOW_PLAYING_COLOR),
+                NowPlayingText(Timer::new(NOW_PLAYING_DURATION, TimerMode::Once)),
+            ));
+        }
+        return;
+    }
+    for (entity, mut now_playing, mut text_color) in text_query.iter_mut() {
+        if now_playing.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let alpha = (now_playing.0.remaining_secs() / NOW_PLAYING_FADE.as_secs_f32()).min(1.0);
+        text_color.0 = NOW_PLAYING_COLOR.with_alpha(alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::LevelState;
+    use crate::headless::Harness;
+
+    #[test]
+    fn playback_position_wraps_back_to_the_loop_start() {
+        let info = MusicTrack::Stage1.info();
+        let loop_length = info.loop_end.unwrap() - info.loop_start;
+
+        assert_eq!(info.position(Duration::from_secs(5)), Duration::from_secs(5));
+        assert_eq!(info.position(info.loop_end.unwrap()), info.loop_start);
+        assert_eq!(info.position(info.loop_end.unwrap() + loop_length + Duration::from_secs(1)), info.loop_start + Duration::from_secs(1));
+    }
+
+    // the length of a wav file, read from its header
+    fn wav_duration(bytes: &[u8]) -> Duration {
+        let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let read_u16 = |at: usize| u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap());
+        assert_eq!(&bytes[0..4], b"RIFF");
+        let mut chunk = 12;
+        let mut format = None;
+        while chunk + 8 <= bytes.len() {
+            let size = read_u32(chunk + 4) as usize;
+            match &bytes[chunk..chunk + 4] {
+                // sample rate and bytes per frame
+                b"fmt " => format = Some((read_u32(chunk + 12), read_u16(chunk + 20))),
+                b"data" => {
+                    let (sample_rate, frame_size) = format.expect("data before fmt");
+                    return Duration::from_secs_f64(size as f64 / frame_size as f64 / sample_rate as f64);
+                },
+                _ => {},
+            }
+            chunk += 8 + size + size % 2;
+        }
+        panic!("no data chunk");
+    }
+
+    #[test]
+    fn every_track_is_in_the_assets_and_loops_inside_its_file() {
+        for track in MusicTrack::ALL {
+            let info = track.info();
+            let bytes = fs::read(format!("assets/{}", info.path))
+                .unwrap_or_else(|error| panic!("{} is missing: {}", info.path, error));
+            let duration = wav_duration(&bytes);
+
+            assert!(info.loop_start < duration, "{} loops from past its end", info.path);
+            if let Some(loop_end) = info.loop_end {
+                assert!(info.loop_start < loop_end && loop_end <= duration, "{} loops to past its end", info.path);
+            }
+        }
+    }
+
+    #[test]
+    fn unlocks_survive_an_encode_decode_round_trip() {
+        let mut unlocks = MusicUnlocks::default();
+        assert!(unlocks.is_unlocked(MusicTrack::Title));
+        assert!(!unlocks.unlock(MusicTrack::Title));
+        assert!(unlocks.unlock(MusicTrack::Rumia));
+        assert!(!unlocks.unlock(MusicTrack::Rumia));
+
+        assert_eq!(MusicUnlocks::decode(&unlocks.encode()).unwrap(), unlocks);
+        assert!(!unlocks.is_unlocked(MusicTrack::Stage1));
+        assert!(MusicUnlocks::decode("unlocked stage9").is_err());
+    }
+
+    #[test]
+    fn the_music_stays_paused_through_the_options_opened_from_pause() {
+        fn visit(harness: &mut Harness, state: GameState) -> bool {
+            harness.world_mut().resource_mut::<NextState<GameState>>().set(state);
+            harness.step(1);
+            harness.world_mut().resource::<MusicPaused>().0
+        }
+        let mut harness = Harness::new(LevelState::Level1, 1);
+
+        assert!(visit(&mut harness, GameState::Paused));
+        assert!(visit(&mut harness, GameState::OptionsMenu));
+        assert!(visit(&mut harness, GameState::KeyConfigMenu));
+        assert!(visit(&mut harness, GameState::OptionsMenu));
+        assert!(visit(&mut harness, GameState::Paused));
+        assert!(!visit(&mut harness, GameState::PlayingGame));
+    }
+}
@@ -1,11 +1,23 @@
 use std::f32::consts::PI;
 use std::time::Duration;
-use crate::bullet::{props_for_bullet_type, Bullet};
+use crate::bullet::{bullet_hit_circle, props_for_bullet_type, Bullet};
+use crate::bosses::boss::Boss;
+use crate::enemy::Enemy;
+use crate::input::{GameButton, PlayerInput};
+use crate::laser::Laser;
+use crate::launch_options::LaunchOptions;
+use crate::player_stats::PlayerStats;
+use crate::movement_patterns::homing::create_homing_pattern;
+use crate::movement_patterns::straight_line::create_straight_line_pattern;
+use crate::movement_patterns::{run_movement_pattern, set_homing_target, MovementPatterns};
+use crate::movement_patterns::MovementPatterns::HomingPattern;
 use crate::game::{GameObject, FRAME_BORDER_BOTTOM, FRAME_BORDER_LEFT, FRAME_BORDER_RIGHT, FRAME_BORDER_TOP};
 use crate::resources::sprites::{set_animation_frames, AnimationIndices, Sprites};
-use bevy::math::bounding::{BoundingCircle, IntersectsVolume};
+use crate::resources::sounds::{PlaySoundEvent, SoundEffect};
+use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume};
 use bevy::prelude::*;
-use crate::GameState;
+
+const BOMB_SECONDS: f32 = 2.0;
 
 #[derive(Event)]
 pub struct PlayerDeathEvent;
@@ -13,26 +25,52 @@ pub struct PlayerDeathEvent;
 #[derive(Event)]
 pub struct PlayerContinueEvent;
 
-#[derive(Component)]
+#[derive(Event)]
+pub struct GrazeEvent;
+
+#[derive(Component, Clone)]
+pub struct Grazed;
+
+#[derive(Component, Clone)]
 pub struct PlayerShotTimer(Timer);
 
-#[derive(Component)]
+#[derive(Component, Clone)]
+pub struct PlayerHomingShotTimer(Timer);
+
+#[derive(Component, Clone)]
 pub struct PlayerRespawnTimer(pub Timer);
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct PlayerInvincibilityTimer(Timer);
 
-#[derive(Component)]
+// present while a bomb is going off
+#[derive(Component, Clone)]
+pub struct PlayerBombTimer(Timer);
+
+#[derive(Resource, Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum Character {
+    #[default]
+    Remilia,
+}
+
+#[derive(Resource, Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum ShotType {
+    #[default]
+    Forward,
+    // a fan of weaker shots that home in on the nearest enemy joins the forward shot
+    Homing,
+}
+
+#[derive(Component, Clone)]
 pub struct Player {
     pub full_movement_speed: f32,
     pub focused_speed: f32,
     pub hit_circle_radius: f32,
+    pub graze_radius: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct PlayerShot {
-    pub speed: f32,
-    pub angle: f32,
     pub damage: i32,
 }
 
@@ -46,6 +84,7 @@ pub fn spawn_player(commands: &mut Commands, sprites: &ResMut<Sprites>) {
             full_movement_speed: 200.0,
             focused_speed: 60.0,
             hit_circle_radius: 5.0,
+            graze_radius: 24.0,
         },
         Transform::from_xyz(-128.0, -150.0, 0.5),
         sprites.remilia.clone(),
@@ -53,6 +92,7 @@ pub fn spawn_player(commands: &mut Commands, sprites: &ResMut<Sprites>) {
         sprites.remilia.animation_indices.clone(),
         sprites.remilia.animation_timer.clone(),
         PlayerShotTimer(Timer::new(Duration::from_millis(100), TimerMode::Once)),
+        PlayerHomingShotTimer(Timer::new(Duration::from_millis(300), TimerMode::Once)),
         GameObject,
     ));
 }
@@ -60,35 +100,31 @@ pub fn spawn_player(commands: &mut Commands, sprites: &ResMut<Sprites>) {
 pub fn move_player(
     time: Res<Time>,
     mut player_query: Query<(&mut Player, &mut Transform)>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut game_state: ResMut<NextState<GameState>>,
+    player_input: Res<PlayerInput>,
 ) {
     for (player, mut transform) in &mut player_query {
-        let speed = if keyboard.pressed(KeyCode::ShiftLeft) { player.focused_speed } else { player.full_movement_speed };
-        if keyboard.pressed(KeyCode::ArrowUp) && transform.translation.y < FRAME_BORDER_TOP {
+        let speed = if player_input.pressed(GameButton::Focus) { player.focused_speed } else { player.full_movement_speed };
+        if player_input.pressed(GameButton::Up) && transform.translation.y < FRAME_BORDER_TOP {
             transform.translation.y += speed * time.delta_secs();
         }
-        if keyboard.pressed(KeyCode::ArrowDown) && transform.translation.y > FRAME_BORDER_BOTTOM {
+        if player_input.pressed(GameButton::Down) && transform.translation.y > FRAME_BORDER_BOTTOM {
             transform.translation.y -= speed * time.delta_secs();
         }
-        if keyboard.pressed(KeyCode::ArrowLeft) && transform.translation.x > FRAME_BORDER_LEFT {
+        if player_input.pressed(GameButton::Left) && transform.translation.x > FRAME_BORDER_LEFT {
             transform.translation.x -= speed * time.delta_secs();
         }
-        if keyboard.pressed(KeyCode::ArrowRight) && transform.translation.x < FRAME_BORDER_RIGHT {
+        if player_input.pressed(GameButton::Right) && transform.translation.x < FRAME_BORDER_RIGHT {
             transform.translation.x += speed * time.delta_secs();
         }
-        if keyboard.just_pressed(KeyCode::Escape) {
-            game_state.set(GameState::Paused);
-        }
     }
 }
 
 pub fn switch_player_sprite(
     mut player_query: Query<(&Player, &mut Sprite, &mut AnimationIndices)>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    player_input: Res<PlayerInput>,
 ) {
-    let left_pressed = keyboard.pressed(KeyCode::ArrowLeft);
-    let right_pressed = keyboard.pressed(KeyCode::ArrowRight);
+    let left_pressed = player_input.pressed(GameButton::Left);
+    let right_pressed = player_input.pressed(GameButton::Right);
     for (_player, mut sprite, mut animation_indices) in &mut player_query {
         if left_pressed && !right_pressed {
             set_animation_frames(&mut animation_indices, 4, 7);
@@ -102,29 +138,93 @@ pub fn switch_player_sprite(
         }
     }
 }
+pub fn player_hit_circle(player: &Player, player_transform: &Transform) -> BoundingCircle {
+    BoundingCircle::new(player_transform.translation.truncate(), player.hit_circle_radius)
+}
+
+pub fn shot_hit_box(shot_sprite: &Sprite, shot_transform: &Transform) -> Aabb2d {
+    Aabb2d::new(
+        shot_transform.translation.truncate(),
+        shot_sprite.rect.unwrap().half_size(),
+    )
+}
+
 pub fn check_bullet_player_collision(
     mut commands: Commands,
     player_query: Query<(&Player, &Transform, Entity)>,
     bullet_query: Query<(&Bullet, &Transform, Entity)>,
     mut player_death_event_writer:  EventWriter<PlayerDeathEvent>,
     invincibility_timer_query: Query<&PlayerInvincibilityTimer>,
+    launch_options: Res<LaunchOptions>,
 ) {
-    if invincibility_timer_query.iter().count() > 0 { return }
+    if launch_options.invincible || invincibility_timer_query.iter().count() > 0 { return }
 
     for (player, player_transform, player_entity) in &mut player_query.iter() {
         for (bullet, bullet_transform, bullet_entity) in bullet_query.iter() {
-            let bullet_props = props_for_bullet_type(&bullet.bullet_type);
-            let player_hit_circle = BoundingCircle::new(player_transform.translation.truncate(), player.hit_circle_radius);
-            let bullet_hit_circle = BoundingCircle::new(bullet_transform.translation.truncate(), bullet_props.hit_circle_radius);
-
-            if player_hit_circle.intersects(&bullet_hit_circle) {
-                commands.entity(player_entity).try_despawn();
+            if player_hit_circle(player, player_transform).intersects(&bullet_hit_circle(bullet, bullet_transform)) {
+                kill_player(&mut commands, player_entity, &mut player_death_event_writer);
                 commands.entity(bullet_entity).try_despawn();
-                commands.spawn((
-                    PlayerRespawnTimer(Timer::from_seconds(0.5, TimerMode::Once)),
-                    GameObject
-                ));
-                player_death_event_writer.send(PlayerDeathEvent);
+            }
+        }
+    }
+}
+
+pub fn check_laser_player_collision(
+    mut commands: Commands,
+    player_query: Query<(&Player, &Transform, Entity)>,
+    laser_query: Query<&Laser>,
+    mut player_death_event_writer: EventWriter<PlayerDeathEvent>,
+    invincibility_timer_query: Query<&PlayerInvincibilityTimer>,
+    launch_options: Res<LaunchOptions>,
+) {
+    if launch_options.invincible || invincibility_timer_query.iter().count() > 0 { return }
+
+    for (player, player_transform, player_entity) in player_query.iter() {
+        let player_position = player_transform.translation.truncate();
+        for laser in laser_query.iter() {
+            let hit_width = laser.hit_width();
+            if hit_width > 0.0 && laser.distance_to(player_position) <= player.hit_circle_radius + hit_width / 2.0 {
+                kill_player(&mut commands, player_entity, &mut player_death_event_writer);
+                return;
+            }
+        }
+    }
+}
+
+fn kill_player(commands: &mut Commands, player_entity: Entity, player_death_event_writer: &mut EventWriter<PlayerDeathEvent>) {
+    commands.entity(player_entity).try_despawn();
+    commands.spawn((
+        PlayerRespawnTimer(Timer::from_seconds(0.5, TimerMode::Once)),
+        GameObject
+    ));
+    player_death_event_writer.send(PlayerDeathEvent);
+}
+
+pub fn check_graze(
+    mut commands: Commands,
+    player_query: Query<(&Player, &Transform)>,
+    bullet_query: Query<(&Bullet, &Transform, Entity), Without<Grazed>>,
+    mut laser_query: Query<&mut Laser>,
+    mut graze_event_writer: EventWriter<GrazeEvent>,
+) {
+    for (player, player_transform) in player_query.iter() {
+        let player_position = player_transform.translation.truncate();
+        for (bullet, bullet_transform, bullet_entity) in bullet_query.iter() {
+            let bullet_props = props_for_bullet_type(&bullet.bullet_type);
+            let distance = player_position.distance(bullet_transform.translation.truncate());
+            if distance <= player.graze_radius + bullet_props.hit_circle_radius {
+                commands.entity(bullet_entity).insert(Grazed);
+                graze_event_writer.send(GrazeEvent);
+            }
+        }
+        for mut laser in laser_query.iter_mut() {
+            let hit_width = laser.hit_width();
+            if hit_width > 0.0
+                && laser.graze_timer.finished()
+                && laser.distance_to(player_position) <= player.graze_radius + hit_width / 2.0
+            {
+                laser.graze_timer.reset();
+                graze_event_writer.send(GrazeEvent);
             }
         }
     }
@@ -140,7 +240,10 @@ pub fn respawn_player(
         if respawn_timer.0.tick(time.delta()).just_finished() {
             spawn_player(&mut commands, &sprites);
             commands.entity(player_respawn).despawn();
-            commands.spawn(PlayerInvincibilityTimer(Timer::from_seconds(2.0, TimerMode::Once)));
+            commands.spawn((
+                PlayerInvincibilityTimer(Timer::from_seconds(2.0, TimerMode::Once)),
+                GameObject,
+            ));
         }
     }
 }
@@ -163,23 +266,65 @@ pub fn respawn_invincibility(
     }
 }
 
+pub fn use_bomb(
+    mut commands: Commands,
+    player_input: Res<PlayerInput>,
+    mut player_stats: ResMut<PlayerStats>,
+    player_query: Query<(), With<Player>>,
+    bomb_query: Query<(), With<PlayerBombTimer>>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    if player_input.pressed(GameButton::Bomb) && player_stats.bombs > 0 && !player_query.is_empty() && bomb_query.is_empty() {
+        player_stats.bombs -= 1;
+        commands.spawn((
+            PlayerBombTimer(Timer::from_seconds(BOMB_SECONDS, TimerMode::Once)),
+            GameObject,
+        ));
+        play_sound_event_writer.send(PlaySoundEvent(SoundEffect::Bomb));
+    }
+}
+
+// a bomb wipes every bullet and laser off the playfield for as long as it lasts
+pub fn clear_bullets_during_bomb(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bomb_query: Query<(&mut PlayerBombTimer, Entity)>,
+    bullet_query: Query<Entity, With<Bullet>>,
+    laser_query: Query<Entity, With<Laser>>,
+) {
+    for (mut timer, bomb) in bomb_query.iter_mut() {
+        for bullet in bullet_query.iter() {
+            commands.entity(bullet).despawn();
+        }
+        for laser in laser_query.iter() {
+            commands.entity(laser).despawn_recursive();
+        }
+        if timer.0.tick(time.delta()).finished() {
+            commands.entity(bomb).despawn();
+        }
+    }
+}
+
 pub fn fire_shot(
     mut commands: Commands,
     sprites: Res<Sprites>,
     time: Res<Time>,
-    mut player_query: Query<(&mut Player, &mut Transform, &mut PlayerShotTimer)>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    mut player_query: Query<(&mut Player, &mut Transform, &mut PlayerShotTimer, &mut PlayerHomingShotTimer)>,
+    player_input: Res<PlayerInput>,
+    player_stats: Res<PlayerStats>,
+    shot_type: Res<ShotType>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
 ) {
-    for (_player, transform, mut shot_timer) in &mut player_query.iter_mut() {
-        if shot_timer.0.tick(time.delta()).finished() && keyboard.pressed(KeyCode::KeyZ) {
+    for (_player, transform, mut shot_timer, mut homing_shot_timer) in &mut player_query.iter_mut() {
+        if shot_timer.0.tick(time.delta()).finished() && player_input.pressed(GameButton::Shot) {
+            play_sound_event_writer.send(PlaySoundEvent(SoundEffect::PlayerShot));
             let shot_angle = PI / 2.0;
             commands.spawn((
                 Name::new("PlayerShot"),
                 PlayerShot {
-                    speed: 1000.0,
-                    angle: shot_angle,
                     damage: 2,
                 },
+                create_straight_line_pattern(Rot2::radians(shot_angle), 1000.0),
                 sprites.blue_fang_shot.clone(),
                 Transform::from_xyz(transform.translation.x, transform.translation.y, 0.4)
                     .with_rotation(Quat::from_rotation_z(shot_angle)),
@@ -187,17 +332,82 @@ pub fn fire_shot(
             ));
             shot_timer.0.reset();
         }
+        if *shot_type == ShotType::Homing && homing_shot_timer.0.tick(time.delta()).finished() && player_input.pressed(GameButton::Shot) {
+            // every power level adds another homing shot, fanned out over the same spread
+            let homing_shots = player_stats.power.max(1) + 1;
+            for i in 0..homing_shots {
+                let shot_angle = PI / 2.0 - PI / 6.0 + i as f32 * (PI / 3.0) / (homing_shots - 1) as f32;
+                commands.spawn((
+                    Name::new("PlayerHomingShot"),
+                    PlayerShot {
+                        damage: 1,
+                    },
+                    create_homing_pattern(Rot2::radians(shot_angle), 500.0, 6.0, None),
+                    sprites.blue_fang_shot.clone(),
+                    Transform::from_xyz(transform.translation.x, transform.translation.y, 0.4)
+                        .with_rotation(Quat::from_rotation_z(shot_angle))
+                        .with_scale(Vec3::splat(0.6)),
+                    GameObject,
+                ));
+            }
+            homing_shot_timer.0.reset();
+        }
     }
 }
 
-pub fn move_shot(
+pub fn move_shots(
     time: Res<Time>,
-    mut shot_query: Query<(&mut PlayerShot, &mut Transform)>,
+    mut shot_query: Query<(&mut MovementPatterns, &mut Transform), With<PlayerShot>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<PlayerShot>)>,
+    boss_query: Query<&Transform, (With<Boss>, Without<PlayerShot>)>,
 ) {
-    for (player_shot, mut transform) in &mut shot_query.iter_mut() {
-        let movement_direction = Vec3::new(player_shot.angle.cos(), player_shot.angle.sin(), 0.0);
-        let movement_distance = player_shot.speed * time.delta_secs();
-        let translation_delta = movement_direction * movement_distance;
-        transform.translation += translation_delta;
+    for (mut movement_pattern, mut transform) in shot_query.iter_mut() {
+        if matches!(*movement_pattern, HomingPattern(_)) {
+            let shot_position = transform.translation.truncate();
+            let nearest_target = enemy_query.iter()
+                .chain(boss_query.iter())
+                .map(|target_transform| target_transform.translation.truncate())
+                .min_by(|a, b| a.distance_squared(shot_position).total_cmp(&b.distance_squared(shot_position)));
+            set_homing_target(&mut movement_pattern, nearest_target);
+        }
+        run_movement_pattern(&mut movement_pattern, &mut transform, &time, false);
+        if let HomingPattern(homing) = &*movement_pattern {
+            transform.rotation = Quat::from_rotation_z(homing.angle.as_radians());
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bullet::Bullet;
+    use crate::game::LevelState;
+    use crate::headless::{Harness, SECONDS};
+    use crate::launch_options::LaunchOptions;
+    use crate::player_stats::{PlayerStats, STARTING_BOMB_COUNT};
+
+    #[test]
+    fn standing_still_under_fire_kills_the_player() {
+        let mut harness = Harness::new(LevelState::Level1, 1);
+
+        harness.step(10 * SECONDS);
+
+        assert!(harness.player_deaths > 0);
+    }
+
+    #[test]
+    fn a_bomb_clears_the_bullets_and_uses_up_one_bomb() {
+        let mut harness = Harness::new(LevelState::Level1, 1);
+        harness.world_mut().resource_mut::<LaunchOptions>().invincible = true;
+        harness.step(3 * SECONDS);
+        assert!(harness.count::<Bullet>() > 0);
+
+        let mut bombing = PlayerInput::default();
+        bombing.set(GameButton::Bomb, true);
+        harness.hold(bombing);
+        harness.step(1);
+
+        assert_eq!(harness.count::<Bullet>(), 0);
+        assert_eq!(harness.world_mut().resource::<PlayerStats>().bombs, STARTING_BOMB_COUNT - 1);
+    }
+}
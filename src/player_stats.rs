@@ -1,42 +1,139 @@
-use crate::player::{PlayerContinueEvent, PlayerDeathEvent};
+use crate::player::{GrazeEvent, PlayerContinueEvent, PlayerDeathEvent};
 use crate::resources::sprites::Sprites;
 use crate::GameState;
+use bevy::ecs::entity::EntityHashMap;
 use bevy::math::Vec3;
-use bevy::prelude::{Commands, Component, Entity, EventReader, Name, NextState, ResMut, Resource, Transform};
+use bevy::prelude::{Commands, Component, Entity, EventReader, Name, NextState, Query, Res, ResMut, Resource, Transform, Visibility};
+use crate::enemy::EnemyDeathEvent;
 use crate::game::GameObject;
+use crate::practice::PracticeSession;
+use crate::snapshot::RemapEntities;
 
-#[derive(Component)]
-struct PlayerLifeCounter;
+#[derive(Component, Clone)]
+pub struct PlayerLifeCounter;
 
-#[derive(Resource)]
+#[derive(Component, Clone)]
+pub struct PlayerBombCounter(u32);
+
+#[derive(Resource, Clone)]
 pub struct PlayerStats {
     pub starting_life_count: usize,
     pub lives: Vec<Entity>,
+    pub bombs: u32,
+    pub power: u32,
+    pub graze: u32,
+    pub score: u64,
+    pub continues: u32,
+    // enemies shot down since the current stage began
+    pub stage_kills: u32,
+}
+
+// what carries over from a cleared stage into the next one, which otherwise starts from scratch
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct StageHandoff {
+    pub lives: usize,
+    pub bombs: u32,
+    pub power: u32,
+    pub graze: u32,
+    pub score: u64,
+    pub continues: u32,
+}
+
+impl StageHandoff {
+    pub fn new(player_stats: &PlayerStats) -> Self {
+        Self {
+            lives: player_stats.lives.len(),
+            bombs: player_stats.bombs,
+            power: player_stats.power,
+            graze: player_stats.graze,
+            score: player_stats.score,
+            continues: player_stats.continues,
+        }
+    }
 }
 
-const STARTING_LIFE_COUNT: usize = 2;
+pub const STARTING_LIFE_COUNT: usize = 2;
+pub const STARTING_BOMB_COUNT: u32 = 3;
+pub const MAX_BOMBS: u32 = 8;
+pub const STARTING_POWER: u32 = 1;
+pub const MAX_POWER: u32 = 4;
+pub const CREDITS: u32 = 3;
+const ENEMY_KILL_POINTS: u64 = 100;
+const GRAZE_POINTS: u64 = 10;
 
 impl Default for PlayerStats {
     fn default() -> Self {
         Self {
             starting_life_count: STARTING_LIFE_COUNT,
             lives: Default::default(),
+            bombs: STARTING_BOMB_COUNT,
+            power: STARTING_POWER,
+            graze: 0,
+            score: 0,
+            continues: 0,
+            stage_kills: 0,
+        }
+    }
+}
+
+impl PlayerStats {
+    pub fn credits_left(&self) -> u32 {
+        CREDITS.saturating_sub(self.continues)
+    }
+}
+
+impl RemapEntities for PlayerStats {
+    fn remap_entities(&mut self, entity_map: &EntityHashMap<Entity>) {
+        for life_counter in self.lives.iter_mut() {
+            if let Some(entity) = entity_map.get(life_counter) {
+                *life_counter = *entity;
+            }
         }
     }
 }
 
 pub fn initialize_player_stats(
-    commands: Commands,
+    mut commands: Commands,
     mut player_stats: ResMut<PlayerStats>,
     sprites: ResMut<Sprites>,
+    practice: Option<Res<PracticeSession>>,
+    handoff: Option<Res<StageHandoff>>,
 ) {
-    reset_player_lives(commands, &mut player_stats, sprites);
+    player_stats.stage_kills = 0;
+    spawn_bomb_counters(&mut commands, &sprites);
+    if let Some(handoff) = handoff {
+        player_stats.bombs = handoff.bombs;
+        player_stats.power = handoff.power;
+        player_stats.graze = handoff.graze;
+        player_stats.score = handoff.score;
+        player_stats.continues = handoff.continues;
+        commands.remove_resource::<StageHandoff>();
+        reset_player_lives(commands, &mut player_stats, sprites, handoff.lives);
+        return;
+    }
+    match practice {
+        Some(practice) => {
+            player_stats.starting_life_count = practice.lives;
+            player_stats.bombs = practice.bombs;
+            player_stats.power = practice.power;
+        },
+        None => {
+            player_stats.starting_life_count = STARTING_LIFE_COUNT;
+            player_stats.bombs = STARTING_BOMB_COUNT;
+            player_stats.power = STARTING_POWER;
+        },
+    }
+    player_stats.graze = 0;
+    player_stats.score = 0;
+    player_stats.continues = 0;
+    let lives = player_stats.starting_life_count;
+    reset_player_lives(commands, &mut player_stats, sprites, lives);
 }
 
-fn reset_player_lives(mut commands: Commands, player_stats: &mut ResMut<PlayerStats>, sprites: ResMut<Sprites>) {
+fn reset_player_lives(mut commands: Commands, player_stats: &mut ResMut<PlayerStats>, sprites: ResMut<Sprites>, lives: usize) {
     let lives_left_bound = 206.0;
     player_stats.lives.clear();
-    for i in 0..player_stats.starting_life_count {
+    for i in 0..lives {
         player_stats.lives.push(commands.spawn((
             Name::new("PlayerLifeCounter"),
             sprites.life_counter.clone(),
@@ -48,6 +145,31 @@ fn reset_player_lives(mut commands: Commands, player_stats: &mut ResMut<PlayerSt
     }
 }
 
+// one star per bomb in stock, shown or hidden as bombs are gained and used
+fn spawn_bomb_counters(commands: &mut Commands, sprites: &ResMut<Sprites>) {
+    let bombs_left_bound = 206.0;
+    for i in 0..MAX_BOMBS {
+        commands.spawn((
+            Name::new("PlayerBombCounter"),
+            sprites.bomb_counter.clone(),
+            Transform::from_xyz(bombs_left_bound + (i as f32 * 22.0), 139.0, 1.1)
+                .with_scale(Vec3::splat(1.5)),
+            Visibility::Hidden,
+            PlayerBombCounter(i),
+            GameObject,
+        ));
+    }
+}
+
+pub fn draw_bomb_counters(
+    player_stats: Res<PlayerStats>,
+    mut counter_query: Query<(&PlayerBombCounter, &mut Visibility)>,
+) {
+    for (counter, mut visibility) in counter_query.iter_mut() {
+        *visibility = if counter.0 < player_stats.bombs { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
 pub fn listen_for_player_death(
     mut commands: Commands,
     mut player_stats: ResMut<PlayerStats>,
@@ -72,7 +194,65 @@ pub fn listen_for_player_continue(
     sprites: ResMut<Sprites>,
 ) {
     if !player_continue_event_reader.is_empty() {
-        reset_player_lives(commands, &mut player_stats, sprites);
+        // the last digit of the score shows how many times the run was continued
+        player_stats.continues += 1;
+        player_stats.score = player_stats.continues as u64;
+        let lives = player_stats.starting_life_count;
+        reset_player_lives(commands, &mut player_stats, sprites, lives);
         player_continue_event_reader.clear();
     }
 }
+
+pub fn listen_for_graze(
+    mut player_stats: ResMut<PlayerStats>,
+    mut graze_event_reader: EventReader<GrazeEvent>,
+) {
+    let grazes = graze_event_reader.read().count() as u32;
+    player_stats.graze += grazes;
+    player_stats.score += grazes as u64 * GRAZE_POINTS;
+}
+
+pub fn listen_for_enemy_kills(
+    mut player_stats: ResMut<PlayerStats>,
+    mut enemy_death_event_reader: EventReader<EnemyDeathEvent>,
+) {
+    let kills = enemy_death_event_reader.read().count();
+    player_stats.stage_kills += kills as u32;
+    player_stats.score += kills as u64 * ENEMY_KILL_POINTS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Checkpoint, Difficulty, LevelState};
+    use crate::headless::Harness;
+    use crate::player::{Character, ShotType};
+    use crate::rank::RankEnabled;
+    use crate::replay::ReplayHeader;
+
+    #[test]
+    fn a_stage_handoff_carries_the_players_stats_into_the_next_stage() {
+        let handoff = StageHandoff {
+            lives: 4,
+            bombs: 1,
+            power: 3,
+            graze: 250,
+            score: 123_450,
+            continues: 1,
+        };
+        let mut harness = Harness::from_header(ReplayHeader {
+            seed: 1,
+            level: LevelState::Level1,
+            checkpoint: Checkpoint::Start,
+            character: Character::Remilia,
+            shot_type: ShotType::Forward,
+            difficulty: Difficulty::Normal,
+            rank: RankEnabled::default(),
+            handoff: Some(handoff.clone()),
+        });
+
+        assert_eq!(StageHandoff::new(harness.world_mut().resource::<PlayerStats>()), handoff);
+        assert_eq!(harness.count::<PlayerLifeCounter>(), 4);
+        assert!(!harness.world_mut().contains_resource::<StageHandoff>());
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use bevy::prelude::*;
+use crate::game::{Checkpoint, ChosenLevel, Difficulty, LevelState, StageClearEvent};
+use crate::player::PlayerDeathEvent;
+use crate::player_stats::{PlayerStats, MAX_BOMBS, MAX_POWER, STARTING_BOMB_COUNT, STARTING_POWER};
+use crate::replay::ReplayPlayback;
+use crate::settings::{data_path, save_data_file};
+use crate::simulation::SimulationSet;
+use crate::GameState;
+
+pub const PRACTICE_RECORDS_FILE_NAME: &str = "practice.txt";
+pub const MAX_PRACTICE_LIVES: usize = 8;
+const FIRST_LEVEL: LevelState = LevelState::Level1;
+
+pub struct PracticeSection {
+    pub name: &'static str,
+    pub key: &'static str,
+    pub level: LevelState,
+    pub checkpoint: Checkpoint,
+}
+
+pub static PRACTICE_SECTIONS: [PracticeSection; 3] = [
+    PracticeSection { name: "Stage 1", key: "level1", level: LevelState::Level1, checkpoint: Checkpoint::Start },
+    PracticeSection { name: "Rumia Spell 1", key: "level1-rumia-spell1", level: LevelState::Level1, checkpoint: Checkpoint::Spell(1) },
+    PracticeSection { name: "Rumia Spell 2", key: "level1-rumia-spell2", level: LevelState::Level1, checkpoint: Checkpoint::Spell(2) },
+];
+
+impl PracticeSection {
+    pub fn is_spell(&self) -> bool {
+        matches!(self.checkpoint, Checkpoint::Spell(_))
+    }
+
+    // records are kept apart for each difficulty
+    pub fn record_key(&self, difficulty: Difficulty) -> String {
+        format!("{}-{}", difficulty.key(), self.key)
+    }
+}
+
+// what the player starts a practice run with, kept between runs while the menu is open.
+// starting lives are a saved setting instead
+#[derive(Resource, Clone, Copy)]
+pub struct PracticeSettings {
+    pub bombs: u32,
+    pub power: u32,
+}
+
+impl Default for PracticeSettings {
+    fn default() -> Self {
+        Self {
+            bombs: STARTING_BOMB_COUNT,
+            power: STARTING_POWER,
+        }
+    }
+}
+
+impl PracticeSettings {
+    pub fn change_bombs(&mut self, delta: i32) {
+        self.bombs = self.bombs.saturating_add_signed(delta).min(MAX_BOMBS);
+    }
+
+    pub fn change_power(&mut self, delta: i32) {
+        self.power = self.power.saturating_add_signed(delta).min(MAX_POWER);
+    }
+}
+
+// present for the length of a practice run, which ends back on the practice menu
+#[derive(Resource)]
+pub struct PracticeSession {
+    pub section: usize,
+    pub lives: usize,
+    pub bombs: u32,
+    pub power: u32,
+    missed: bool,
+}
+
+impl PracticeSession {
+    pub fn section(&self) -> &'static PracticeSection {
+        &PRACTICE_SECTIONS[self.section]
+    }
+
+    pub fn is_spell(&self) -> bool {
+        self.section().is_spell()
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct SectionRecord {
+    pub best_score: u64,
+    pub attempts: u32,
+    pub captures: u32,
+}
+
+// per-section results and which stages have been reached, kept in a small text file
+#[derive(Default, Debug, PartialEq)]
+pub struct PracticeRecords {
+    pub sections: HashMap<String, SectionRecord>,
+    pub unlocked_levels: Vec<String>,
+}
+
+impl PracticeRecords {
+    pub fn load() -> Self {
+        match fs::read_to_string(data_path(PRACTICE_RECORDS_FILE_NAME)) {
+            Ok(text) => Self::decode(&text).unwrap_or_else(|error| {
+                error!("Could not read practice records: {}", error);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        save_data_file(PRACTICE_RECORDS_FILE_NAME, self.encode())
+    }
+
+    pub fn encode(&self) -> String {
+        let mut lines: Vec<String> = self.unlocked_levels.iter()
+            .map(|level| format!("unlocked {}", level))
+            .collect();
+        let mut keys: Vec<&String> = self.sections.keys().collect();
+        keys.sort();
+        for key in keys {
+            let record = self.sections[key];
+            lines.push(format!("section {} {} {} {}", key, record.best_score, record.attempts, record.captures));
+        }
+        lines.join("\n")
+    }
+
+    pub fn decode(text: &str) -> io::Result<Self> {
+        let mut records = Self::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["unlocked", level] => records.unlocked_levels.push(level.to_string()),
+                ["section", key, best_score, attempts, captures] => {
+                    records.sections.insert(key.to_string(), SectionRecord {
+                        best_score: parse_field(best_score)?,
+                        attempts: parse_field(attempts)?,
+                        captures: parse_field(captures)?,
+                    });
+                },
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unexpected line {}", line))),
+            }
+        }
+        Ok(records)
+    }
+
+    pub fn is_unlocked(&self, level: LevelState) -> bool {
+        level == FIRST_LEVEL || self.unlocked_levels.iter().any(|key| key == level_key(level))
+    }
+
+    pub fn record(&mut self, key: &str, score: u64, captured: bool) {
+        let record = self.sections.entry(key.to_string()).or_default();
+        record.best_score = record.best_score.max(score);
+        record.attempts += 1;
+        if captured {
+            record.captures += 1;
+        }
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str) -> io::Result<T> {
+    field.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid number {}", field)))
+}
+
+fn level_key(level: LevelState) -> &'static str {
+    match level {
+        LevelState::None => "none",
+        LevelState::Level1 => "level1",
+    }
+}
+
+pub fn practice_plugin(app: &mut App) {
+    app
+        .init_resource::<PracticeSettings>()
+        .add_systems(OnEnter(GameState::StartingGame), (
+            reset_practice_run.run_if(resource_exists::<PracticeSession>),
+            unlock_chosen_level.run_if(not(resource_exists::<PracticeSession>)),
+        ))
+        .add_systems(FixedUpdate, (
+            listen_for_practice_miss,
+            finish_practice_on_clear,
+        ).chain().after(SimulationSet::Stage).run_if(in_state(GameState::PlayingGame).and(resource_exists::<PracticeSession>)))
+        .add_systems(OnEnter(GameState::GameOver), finish_practice_on_game_over.run_if(resource_exists::<PracticeSession>))
+        .add_systems(OnEnter(GameState::PracticeMenu), end_practice_session)
+        .add_systems(OnEnter(GameState::MainMenu), end_practice_session)
+    ;
+}
+
+pub fn start_practice(
+    commands: &mut Commands,
+    section: usize,
+    settings: &PracticeSettings,
+    lives: usize,
+    chosen_level: &mut ChosenLevel,
+) {
+    chosen_level.level = PRACTICE_SECTIONS[section].level;
+    chosen_level.checkpoint = PRACTICE_SECTIONS[section].checkpoint;
+    commands.insert_resource(PracticeSession {
+        section,
+        lives,
+        bombs: settings.bombs,
+        power: settings.power,
+        missed: false,
+    });
+}
+
+fn reset_practice_run(
+    mut practice: ResMut<PracticeSession>,
+) {
+    practice.missed = false;
+}
+
+fn unlock_chosen_level(
+    chosen_level: Res<ChosenLevel>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_some() || chosen_level.level == LevelState::None {
+        return;
+    }
+    let mut records = PracticeRecords::load();
+    if !records.is_unlocked(chosen_level.level) {
+        records.unlocked_levels.push(level_key(chosen_level.level).to_string());
+        if let Err(error) = records.save() {
+            error!("Could not save practice records: {}", error);
+        }
+    }
+}
+
+fn listen_for_practice_miss(
+    mut practice: ResMut<PracticeSession>,
+    mut player_death_event_reader: EventReader<PlayerDeathEvent>,
+) {
+    if player_death_event_reader.read().count() > 0 {
+        practice.missed = true;
+    }
+}
+
+fn save_practice_result(practice: &PracticeSession, player_stats: &PlayerStats, difficulty: Difficulty, cleared: bool) {
+    let mut records = PracticeRecords::load();
+    records.record(&practice.section().record_key(difficulty), player_stats.score, cleared && !practice.missed);
+    if let Err(error) = records.save() {
+        error!("Could not save practice records: {}", error);
+    }
+}
+
+fn finish_practice_on_clear(
+    mut stage_clear_event_reader: EventReader<StageClearEvent>,
+    practice: Res<PracticeSession>,
+    player_stats: Res<PlayerStats>,
+    difficulty: Res<Difficulty>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if stage_clear_event_reader.read().count() > 0 {
+        save_practice_result(&practice, &player_stats, *difficulty, true);
+        game_state.set(GameState::PracticeMenu);
+    }
+}
+
+fn finish_practice_on_game_over(
+    practice: Res<PracticeSession>,
+    player_stats: Res<PlayerStats>,
+    difficulty: Res<Difficulty>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    save_practice_result(&practice, &player_stats, *difficulty, false);
+    game_state.set(GameState::PracticeMenu);
+}
+
+fn end_practice_session(
+    mut commands: Commands,
+) {
+    commands.remove_resource::<PracticeSession>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_survive_an_encode_decode_round_trip() {
+        let mut records = PracticeRecords::default();
+        records.unlocked_levels.push("level1".to_string());
+        records.record("level1-rumia-spell2", 1200, true);
+        records.record("level1-rumia-spell2", 800, false);
+        records.record("level1", 5400, false);
+
+        let decoded = PracticeRecords::decode(&records.encode()).unwrap();
+
+        assert_eq!(decoded, records);
+        assert_eq!(decoded.sections["level1-rumia-spell2"], SectionRecord { best_score: 1200, attempts: 2, captures: 1 });
+    }
+
+    #[test]
+    fn malformed_records_are_rejected() {
+        assert!(PracticeRecords::decode("section level1 lots 1 0").is_err());
+        assert!(PracticeRecords::decode("high-score 10").is_err());
+    }
+
+    #[test]
+    fn the_first_stage_is_always_unlocked() {
+        assert!(PracticeRecords::default().is_unlocked(LevelState::Level1));
+        assert!(!PracticeRecords::default().is_unlocked(LevelState::None));
+    }
+}
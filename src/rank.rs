@@ -0,0 +1,122 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::enemy::EnemyDeathEvent;
+use crate::player::PlayerDeathEvent;
+use crate::simulation::SimulationSet;
+use crate::snapshot::snapshot_resource;
+use crate::GameState;
+
+pub const MIN_RANK: f32 = 0.0;
+pub const MAX_RANK: f32 = 1.0;
+const RANK_PER_SECOND: f32 = 1.0 / 300.0;
+const RANK_PER_FAST_KILL: f32 = 0.01;
+// kills later than this after the enemy appeared don't raise rank at all
+const FAST_KILL_WINDOW: Duration = Duration::from_secs(4);
+const RANK_LOST_PER_DEATH: f32 = 0.2;
+// how far the top rank pushes patterns past their written values
+const MAX_SPEED_SCALE: f32 = 1.3;
+const MAX_DENSITY_SCALE: f32 = 1.5;
+
+// whether this run uses rank at all, chosen before the run like the difficulty
+#[derive(Resource, Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub struct RankEnabled(pub bool);
+
+// how hard the run currently is on top of its difficulty, from MIN_RANK to MAX_RANK.
+// it only moves with simulation ticks and events, so identical input gives identical rank
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq)]
+pub struct Rank {
+    pub enabled: bool,
+    pub value: f32,
+}
+
+impl Rank {
+    pub fn speed_scale(&self) -> f32 {
+        self.scale(MAX_SPEED_SCALE)
+    }
+
+    pub fn density_scale(&self) -> f32 {
+        self.scale(MAX_DENSITY_SCALE)
+    }
+
+    // scales a count of bullets or lines by density, never below what the pattern was written with
+    pub fn scale_count(&self, count: usize) -> usize {
+        (count as f32 * self.density_scale()).round() as usize
+    }
+
+    fn scale(&self, maximum: f32) -> f32 {
+        if self.enabled {
+            1.0 + (maximum - 1.0) * self.value
+        } else {
+            1.0
+        }
+    }
+
+    fn change(&mut self, amount: f32) {
+        self.value = (self.value + amount).clamp(MIN_RANK, MAX_RANK);
+    }
+}
+
+pub fn rank_plugin(app: &mut App) {
+    app
+        .init_resource::<Rank>()
+        .init_resource::<RankEnabled>()
+        .add_systems(OnEnter(GameState::StartingGame), reset_rank)
+        .add_systems(FixedUpdate, update_rank
+            .run_if(|rank: Res<Rank>| rank.enabled)
+            .in_set(SimulationSet::Stage))
+        .add_plugins(snapshot_resource::<Rank>)
+    ;
+}
+
+fn reset_rank(
+    mut rank: ResMut<Rank>,
+    rank_enabled: Res<RankEnabled>,
+) {
+    *rank = Rank {
+        enabled: rank_enabled.0,
+        value: MIN_RANK,
+    };
+}
+
+fn update_rank(
+    time: Res<Time>,
+    mut rank: ResMut<Rank>,
+    mut enemy_death_event_reader: EventReader<EnemyDeathEvent>,
+    mut player_death_event_reader: EventReader<PlayerDeathEvent>,
+) {
+    rank.change(time.delta_secs() * RANK_PER_SECOND);
+    for enemy_death in enemy_death_event_reader.read() {
+        let quickness = 1.0 - (enemy_death.age.as_secs_f32() / FAST_KILL_WINDOW.as_secs_f32()).min(1.0);
+        rank.change(quickness * RANK_PER_FAST_KILL);
+    }
+    for _ in player_death_event_reader.read() {
+        rank.change(-RANK_LOST_PER_DEATH);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_rank_leaves_patterns_as_written() {
+        let rank = Rank { enabled: false, value: MAX_RANK };
+
+        assert_eq!(rank.speed_scale(), 1.0);
+        assert_eq!(rank.scale_count(16), 16);
+    }
+
+    #[test]
+    fn rank_scales_patterns_within_bounds() {
+        let mut rank = Rank { enabled: true, value: MIN_RANK };
+        assert_eq!(rank.scale_count(16), 16);
+
+        rank.change(10.0);
+        assert_eq!(rank.value, MAX_RANK);
+        assert_eq!(rank.speed_scale(), MAX_SPEED_SCALE);
+        assert_eq!(rank.scale_count(16), 24);
+
+        rank.change(-10.0);
+        assert_eq!(rank.value, MIN_RANK);
+    }
+}
@@ -0,0 +1,803 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use bevy::prelude::*;
+use crate::bullet::Bullet;
+use crate::enemy::Enemy;
+use crate::game::{Checkpoint, ChosenLevel, Difficulty, GameObject, LevelState, StageClearEvent};
+use crate::launch_options::LaunchOptions;
+use crate::input::{read_player_input, Action, PlayerInput};
+use crate::player::{Character, Player, ShotType};
+use crate::player_stats::{initialize_player_stats, PlayerStats, StageHandoff};
+use crate::practice::PracticeSession;
+use crate::rank::{Rank, RankEnabled};
+use crate::rng::{reseed_game_rng, ChosenSeed, GameRng};
+use crate::settings::data_path;
+use crate::simulation::SimulationSet;
+use crate::snapshot::snapshot_component;
+use crate::time_control::{simulation_running, NORMAL_SPEED_INDEX, SIMULATION_SPEEDS};
+use crate::GameState;
+
+pub const REPLAY_DIRECTORY: &str = "replays";
+pub const REPLAY_EXTENSION: &str = "rpy";
+const REPLAY_MAGIC: &[u8; 4] = b"SRPY";
+const REPLAY_VERSION: u8 = 5;
+const CHECKSUM_INTERVAL: usize = 60;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayHeader {
+    pub seed: u64,
+    pub level: LevelState,
+    pub checkpoint: Checkpoint,
+    pub character: Character,
+    pub shot_type: ShotType,
+    pub difficulty: Difficulty,
+    pub rank: RankEnabled,
+    // the stats brought over from the previous stage, when the run didn't start on this one
+    pub handoff: Option<StageHandoff>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub frames: Vec<PlayerInput>,
+    // (frame, checksum) pairs taken every CHECKSUM_INTERVAL frames while recording
+    pub checksums: Vec<(u32, u32)>,
+}
+
+impl Replay {
+    pub fn new(header: ReplayHeader) -> Self {
+        Self {
+            header,
+            frames: Vec::new(),
+            checksums: Vec::new(),
+        }
+    }
+
+    // inputs are stored as (buttons, run length) pairs since they rarely change from one frame to the next
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.push(REPLAY_VERSION);
+        bytes.extend_from_slice(&self.header.seed.to_le_bytes());
+        bytes.push(level_to_byte(self.header.level));
+        bytes.extend_from_slice(&checkpoint_to_bytes(self.header.checkpoint));
+        bytes.push(character_to_byte(self.header.character));
+        bytes.push(shot_type_to_byte(self.header.shot_type));
+        bytes.push(difficulty_to_byte(self.header.difficulty));
+        bytes.push(self.header.rank.0 as u8);
+        match &self.header.handoff {
+            Some(handoff) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&(handoff.lives as u32).to_le_bytes());
+                bytes.extend_from_slice(&handoff.bombs.to_le_bytes());
+                bytes.extend_from_slice(&handoff.power.to_le_bytes());
+                bytes.extend_from_slice(&handoff.graze.to_le_bytes());
+                bytes.extend_from_slice(&handoff.score.to_le_bytes());
+                bytes.extend_from_slice(&handoff.continues.to_le_bytes());
+            },
+            None => bytes.push(0),
+        }
+
+        let mut runs: Vec<(u8, u16)> = Vec::new();
+        for input in &self.frames {
+            match runs.last_mut() {
+                Some((buttons, length)) if *buttons == input.0 && *length < u16::MAX => *length += 1,
+                _ => runs.push((input.0, 1)),
+            }
+        }
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (buttons, length) in runs {
+            bytes.push(buttons);
+            bytes.extend_from_slice(&length.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&(self.checksums.len() as u32).to_le_bytes());
+        for (frame, checksum) in &self.checksums {
+            bytes.extend_from_slice(&frame.to_le_bytes());
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        if reader.take(REPLAY_MAGIC.len())? != REPLAY_MAGIC {
+            return Err(invalid_data("not a replay file"));
+        }
+        let version = reader.u8()?;
+        if version != REPLAY_VERSION {
+            return Err(invalid_data(&format!("unsupported replay version {}", version)));
+        }
+        let header = ReplayHeader {
+            seed: reader.u64()?,
+            level: level_from_byte(reader.u8()?)?,
+            checkpoint: checkpoint_from_bytes(reader.u8()?, reader.u8()?)?,
+            character: character_from_byte(reader.u8()?)?,
+            shot_type: shot_type_from_byte(reader.u8()?)?,
+            difficulty: difficulty_from_byte(reader.u8()?)?,
+            rank: RankEnabled(reader.u8()? != 0),
+            handoff: match reader.u8()? {
+                0 => None,
+                1 => Some(StageHandoff {
+                    lives: reader.u32()? as usize,
+                    bombs: reader.u32()?,
+                    power: reader.u32()?,
+                    graze: reader.u32()?,
+                    score: reader.u64()?,
+                    continues: reader.u32()?,
+                }),
+                byte => return Err(invalid_data(&format!("unknown handoff marker {}", byte))),
+            },
+        };
+
+        let mut frames = Vec::new();
+        for _ in 0..reader.u32()? {
+            let buttons = reader.u8()?;
+            let length = reader.u16()?;
+            frames.extend(std::iter::repeat_n(PlayerInput(buttons), length as usize));
+        }
+
+        let mut checksums = Vec::new();
+        for _ in 0..reader.u32()? {
+            checksums.push((reader.u32()?, reader.u32()?));
+        }
+
+        Ok(Self { header, frames, checksums })
+    }
+
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let directory = data_path(REPLAY_DIRECTORY);
+        fs::create_dir_all(&directory)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_millis()).unwrap_or_default();
+        let mut path = directory.join(format!("replay_{}.{}", timestamp, REPLAY_EXTENSION));
+        // the counter still sorts after the plain name, so the list stays newest first
+        let mut counter = 1;
+        while path.exists() {
+            path = directory.join(format!("replay_{}_{}.{}", timestamp, counter, REPLAY_EXTENSION));
+            counter += 1;
+        }
+        fs::write(&path, self.encode())?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let slice = self.bytes.get(self.position..self.position + count)
+            .ok_or_else(|| invalid_data("replay file is truncated"))?;
+        self.position += count;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn level_to_byte(level: LevelState) -> u8 {
+    match level {
+        LevelState::None => 0,
+        LevelState::Level1 => 2,
+    }
+}
+
+fn level_from_byte(byte: u8) -> io::Result<LevelState> {
+    match byte {
+        0 => Ok(LevelState::None),
+        2 => Ok(LevelState::Level1),
+        _ => Err(invalid_data(&format!("unknown level {}", byte))),
+    }
+}
+
+fn checkpoint_to_bytes(checkpoint: Checkpoint) -> [u8; 2] {
+    match checkpoint {
+        Checkpoint::Start => [0, 0],
+        Checkpoint::Boss => [1, 0],
+        Checkpoint::Spell(spell) => [2, spell],
+        Checkpoint::PostBoss => [3, 0],
+    }
+}
+
+fn checkpoint_from_bytes(byte: u8, spell: u8) -> io::Result<Checkpoint> {
+    match byte {
+        0 => Ok(Checkpoint::Start),
+        1 => Ok(Checkpoint::Boss),
+        2 => Ok(Checkpoint::Spell(spell)),
+        3 => Ok(Checkpoint::PostBoss),
+        _ => Err(invalid_data(&format!("unknown checkpoint {}", byte))),
+    }
+}
+
+fn character_to_byte(character: Character) -> u8 {
+    match character {
+        Character::Remilia => 0,
+    }
+}
+
+fn character_from_byte(byte: u8) -> io::Result<Character> {
+    match byte {
+        0 => Ok(Character::Remilia),
+        _ => Err(invalid_data(&format!("unknown character {}", byte))),
+    }
+}
+
+fn shot_type_to_byte(shot_type: ShotType) -> u8 {
+    match shot_type {
+        ShotType::Forward => 0,
+        ShotType::Homing => 1,
+    }
+}
+
+fn shot_type_from_byte(byte: u8) -> io::Result<ShotType> {
+    match byte {
+        0 => Ok(ShotType::Forward),
+        1 => Ok(ShotType::Homing),
+        _ => Err(invalid_data(&format!("unknown shot type {}", byte))),
+    }
+}
+
+fn difficulty_to_byte(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Easy => 0,
+        Difficulty::Normal => 1,
+        Difficulty::Hard => 2,
+        Difficulty::Lunatic => 3,
+    }
+}
+
+fn difficulty_from_byte(byte: u8) -> io::Result<Difficulty> {
+    match byte {
+        0 => Ok(Difficulty::Easy),
+        1 => Ok(Difficulty::Normal),
+        2 => Ok(Difficulty::Hard),
+        3 => Ok(Difficulty::Lunatic),
+        _ => Err(invalid_data(&format!("unknown difficulty {}", byte))),
+    }
+}
+
+pub fn list_replays() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(data_path(REPLAY_DIRECTORY))
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == REPLAY_EXTENSION))
+            .collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths.reverse();
+    paths
+}
+
+#[derive(Resource)]
+pub struct ReplayRecording(pub Replay);
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub frame: usize,
+    pub desync_frame: Option<u32>,
+    speed_index: usize,
+    previous_seed: Option<u64>,
+}
+
+impl ReplayPlayback {
+    pub fn speed(&self) -> f32 {
+        SIMULATION_SPEEDS[self.speed_index]
+    }
+}
+
+#[derive(Component, Clone)]
+struct ReplayStatusText;
+
+pub fn replay_plugin(app: &mut App) {
+    app
+        .add_systems(OnEnter(GameState::StartingGame), (
+            start_replay.after(reseed_game_rng).before(initialize_player_stats),
+            spawn_replay_status_text.run_if(resource_exists::<ReplayPlayback>),
+        ))
+        .add_systems(FixedUpdate, (
+            record_replay_input
+                .after(read_player_input)
+                .after(play_back_replay_input)
+                .run_if(resource_exists::<ReplayRecording>),
+            play_back_replay_input.run_if(resource_exists::<ReplayPlayback>),
+        ).in_set(SimulationSet::Input))
+        .add_systems(FixedUpdate, (
+            check_replay_checksum,
+            save_replay_on_stage_clear,
+        ).chain().after(SimulationSet::Stage).run_if(in_state(GameState::PlayingGame).and(simulation_running)))
+        .add_systems(OnEnter(GameState::GameOver), end_replay_on_game_over)
+        .add_systems(OnEnter(GameState::MainMenu), stop_replay)
+        .add_systems(OnEnter(GameState::PracticeMenu), stop_replay)
+        .add_systems(Update, (
+            change_playback_speed,
+            draw_replay_status,
+        ).chain().run_if(in_state(GameState::PlayingGame).and(resource_exists::<ReplayPlayback>)))
+        .add_plugins(snapshot_component::<ReplayStatusText>)
+    ;
+}
+
+pub fn start_replay_playback(
+    commands: &mut Commands,
+    replay: Replay,
+    chosen_seed: &mut ChosenSeed,
+    chosen_level: &mut ChosenLevel,
+) {
+    let previous_seed = chosen_seed.0.replace(replay.header.seed);
+    chosen_level.level = replay.header.level;
+    chosen_level.checkpoint = replay.header.checkpoint;
+    commands.insert_resource(replay.header.character);
+    commands.insert_resource(replay.header.shot_type);
+    commands.insert_resource(replay.header.difficulty);
+    commands.insert_resource(replay.header.rank);
+    match &replay.header.handoff {
+        Some(handoff) => commands.insert_resource(handoff.clone()),
+        None => commands.remove_resource::<StageHandoff>(),
+    }
+    commands.insert_resource(ReplayPlayback {
+        replay,
+        frame: 0,
+        desync_frame: None,
+        speed_index: NORMAL_SPEED_INDEX,
+        previous_seed,
+    });
+}
+
+fn start_replay(
+    mut commands: Commands,
+    game_rng: Res<GameRng>,
+    chosen_level: Res<ChosenLevel>,
+    character: Res<Character>,
+    shot_type: Res<ShotType>,
+    difficulty: Res<Difficulty>,
+    rank_enabled: Res<RankEnabled>,
+    handoff: Option<Res<StageHandoff>>,
+    playback: Option<ResMut<ReplayPlayback>>,
+) {
+    match playback {
+        Some(mut playback) => {
+            playback.frame = 0;
+            playback.desync_frame = None;
+        },
+        None => {
+            commands.insert_resource(ReplayRecording(Replay::new(ReplayHeader {
+                seed: game_rng.seed,
+                level: chosen_level.level,
+                checkpoint: chosen_level.checkpoint,
+                character: *character,
+                shot_type: *shot_type,
+                difficulty: *difficulty,
+                rank: *rank_enabled,
+                handoff: handoff.as_deref().cloned(),
+            })));
+        },
+    }
+}
+
+fn spawn_replay_status_text(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        Name::new("ReplayStatusText"),
+        Text2d::new(""),
+        TextFont {
+            font: asset_server.load("fonts/Super-Cartoon.ttf"),
+            font_size: 16.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Left),
+        Transform::from_xyz(230.0, -220.0, 1.1),
+        ReplayStatusText,
+        GameObject,
+    ));
+}
+
+fn record_replay_input(
+    player_input: Res<PlayerInput>,
+    mut recording: ResMut<ReplayRecording>,
+) {
+    recording.0.frames.push(*player_input);
+}
+
+fn play_back_replay_input(
+    mut player_input: ResMut<PlayerInput>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    match playback.replay.frames.get(playback.frame).copied() {
+        Some(input) => {
+            *player_input = input;
+            playback.frame += 1;
+        },
+        None => {
+            *player_input = PlayerInput::default();
+            game_state.set(GameState::MainMenu);
+        },
+    }
+}
+
+// how many simulation frames of the run so far are recorded or played back
+pub fn replay_frame(world: &World) -> Option<usize> {
+    if let Some(playback) = world.get_resource::<ReplayPlayback>() {
+        return Some(playback.frame);
+    }
+    world.get_resource::<ReplayRecording>().map(|recording| recording.0.frames.len())
+}
+
+// after a rewind, playback carries on from the given frame and a recording forgets everything past it
+pub fn rewind_replay(world: &mut World, frame: usize) {
+    if let Some(mut playback) = world.get_resource_mut::<ReplayPlayback>() {
+        playback.frame = frame;
+        if playback.desync_frame.is_some_and(|desync_frame| desync_frame as usize > frame) {
+            playback.desync_frame = None;
+        }
+    }
+    if let Some(mut recording) = world.get_resource_mut::<ReplayRecording>() {
+        recording.0.frames.truncate(frame);
+        recording.0.checksums.retain(|(checksum_frame, _)| *checksum_frame as usize <= frame);
+    }
+}
+
+fn simulation_checksum(
+    player_query: &Query<&Transform, With<Player>>,
+    bullet_count: usize,
+    enemy_count: usize,
+    player_stats: &PlayerStats,
+    rank: &Rank,
+    game_rng: &GameRng,
+) -> u32 {
+    // FNV-1a over whatever would drift first if playback stopped matching the recording
+    let mut hash: u32 = 0x811c9dc5;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+    };
+    for transform in player_query.iter() {
+        feed(&transform.translation.x.to_bits().to_le_bytes());
+        feed(&transform.translation.y.to_bits().to_le_bytes());
+    }
+    feed(&(bullet_count as u32).to_le_bytes());
+    feed(&(enemy_count as u32).to_le_bytes());
+    feed(&(player_stats.lives.len() as u32).to_le_bytes());
+    feed(&player_stats.graze.to_le_bytes());
+    feed(&rank.value.to_bits().to_le_bytes());
+    feed(&game_rng.gameplay.get_word_pos().to_le_bytes());
+    hash
+}
+
+fn check_replay_checksum(
+    player_query: Query<&Transform, With<Player>>,
+    bullet_query: Query<&Bullet>,
+    enemy_query: Query<&Enemy>,
+    player_stats: Res<PlayerStats>,
+    rank: Res<Rank>,
+    game_rng: Res<GameRng>,
+    recording: Option<ResMut<ReplayRecording>>,
+    playback: Option<ResMut<ReplayPlayback>>,
+) {
+    let checksum = || simulation_checksum(
+        &player_query,
+        bullet_query.iter().count(),
+        enemy_query.iter().count(),
+        &player_stats,
+        &rank,
+        &game_rng,
+    );
+    if let Some(mut recording) = recording {
+        let frame = recording.0.frames.len();
+        if frame > 0 && frame % CHECKSUM_INTERVAL == 0 {
+            let checksum = checksum();
+            recording.0.checksums.push((frame as u32, checksum));
+        }
+    }
+    if let Some(mut playback) = playback {
+        let frame = playback.frame as u32;
+        if playback.desync_frame.is_some() || frame == 0 || playback.frame % CHECKSUM_INTERVAL != 0 {
+            return;
+        }
+        let expected = playback.replay.checksums.iter().find(|(checksum_frame, _)| *checksum_frame == frame).map(|(_, checksum)| *checksum);
+        if expected.is_some_and(|expected| expected != checksum()) {
+            warn!("Replay desynced at frame {}", frame);
+            playback.desync_frame = Some(frame);
+        }
+    }
+}
+
+fn save_replay(
+    commands: &mut Commands,
+    recording: &ReplayRecording,
+    launch_options: &LaunchOptions,
+    practicing: bool,
+) {
+    // invincible and practice runs can't be played back faithfully without the same options,
+    // and practice runs are only recorded so they can be rewound
+    if !launch_options.invincible && !practicing {
+        match recording.0.save() {
+            Ok(path) => info!("Saved replay to {}", path.display()),
+            Err(error) => error!("Could not save replay: {}", error),
+        }
+    }
+    commands.remove_resource::<ReplayRecording>();
+}
+
+fn save_replay_on_stage_clear(
+    mut commands: Commands,
+    mut stage_clear_event_reader: EventReader<StageClearEvent>,
+    recording: Option<Res<ReplayRecording>>,
+    launch_options: Res<LaunchOptions>,
+    practice: Option<Res<PracticeSession>>,
+) {
+    if stage_clear_event_reader.read().count() > 0 {
+        if let Some(recording) = recording {
+            save_replay(&mut commands, &recording, &launch_options, practice.is_some());
+        }
+    }
+}
+
+fn end_replay_on_game_over(
+    mut commands: Commands,
+    recording: Option<Res<ReplayRecording>>,
+    playback: Option<Res<ReplayPlayback>>,
+    launch_options: Res<LaunchOptions>,
+    practice: Option<Res<PracticeSession>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(recording) = recording {
+        save_replay(&mut commands, &recording, &launch_options, practice.is_some());
+    }
+    // a practice run heads back to its own menu instead
+    if playback.is_some() && practice.is_none() {
+        game_state.set(GameState::MainMenu);
+    }
+}
+
+fn stop_replay(
+    mut commands: Commands,
+    playback: Option<Res<ReplayPlayback>>,
+    mut chosen_seed: ResMut<ChosenSeed>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if let Some(playback) = playback {
+        chosen_seed.0 = playback.previous_seed;
+        commands.remove_resource::<ReplayPlayback>();
+    }
+    commands.remove_resource::<ReplayRecording>();
+    time.set_relative_speed(1.0);
+}
+
+fn change_playback_speed(
+    actions: Res<ButtonInput<Action>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if actions.just_pressed(Action::Right) && playback.speed_index + 1 < SIMULATION_SPEEDS.len() {
+        playback.speed_index += 1;
+    } else if actions.just_pressed(Action::Left) && playback.speed_index > 0 {
+        playback.speed_index -= 1;
+    }
+    time.set_relative_speed(playback.speed());
+}
+
+fn draw_replay_status(
+    playback: Res<ReplayPlayback>,
+    mut text_query: Query<&mut Text2d, With<ReplayStatusText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.0 = match playback.desync_frame {
+            Some(frame) => format!("Replay x{}\nDesync at frame {}", playback.speed(), frame),
+            None => format!("Replay x{}", playback.speed()),
+        };
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::headless::{headless_app, MAX_STARTUP_FRAMES};
+    use crate::player_stats::{PlayerStats, StageHandoff};
+    use crate::input::GameButton;
+    use crate::simulation::SIMULATION_HZ;
+
+    const RECORDED_FRAMES: usize = 3 * SIMULATION_HZ as usize;
+
+    fn header() -> ReplayHeader {
+        ReplayHeader {
+            seed: 1234,
+            level: LevelState::Level1,
+            checkpoint: Checkpoint::Spell(2),
+            character: Character::Remilia,
+            shot_type: ShotType::Homing,
+            difficulty: Difficulty::Lunatic,
+            rank: RankEnabled(true),
+            handoff: None,
+        }
+    }
+
+    #[test]
+    fn replays_survive_an_encode_decode_round_trip() {
+        let mut shooting = PlayerInput::default();
+        shooting.set(GameButton::Shot, true);
+        let mut frames = vec![PlayerInput::default(); 3];
+        frames.extend(vec![shooting; 70_000]);
+        let replay = Replay {
+            header: header(),
+            frames,
+            checksums: vec![(60, 42), (120, 7)],
+        };
+
+        assert_eq!(Replay::decode(&replay.encode()).unwrap(), replay);
+    }
+
+    #[test]
+    fn a_stage_handoff_survives_an_encode_decode_round_trip() {
+        let replay = Replay::new(ReplayHeader {
+            handoff: Some(StageHandoff {
+                lives: 4,
+                bombs: 1,
+                power: 3,
+                graze: 250,
+                score: 9_876_543_210,
+                continues: 2,
+            }),
+            ..header()
+        });
+
+        assert_eq!(Replay::decode(&replay.encode()).unwrap(), replay);
+    }
+
+    fn player_translation(world: &mut World) -> Vec3 {
+        world.query_filtered::<&Transform, With<Player>>().single(world).translation
+    }
+
+    // plays the opening on the keyboard with recording on, as a normal run does
+    fn record_run(seed: u64) -> (Replay, Vec3) {
+        let mut app = headless_app();
+        app.update();
+        app.world_mut().resource_mut::<ChosenSeed>().0 = Some(seed);
+        app.world_mut().resource_mut::<ChosenLevel>().level = LevelState::Level1;
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::StartingGame);
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.press(KeyCode::KeyZ);
+        keyboard.press(KeyCode::ArrowLeft);
+        for _ in 0..2 * RECORDED_FRAMES {
+            match replay_frame(app.world()) {
+                Some(frame) if frame >= RECORDED_FRAMES => break,
+                Some(frame) if frame >= RECORDED_FRAMES / 2 => app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::ArrowLeft),
+                _ => {},
+            }
+            app.update();
+        }
+
+        let replay = app.world().resource::<ReplayRecording>().0.clone();
+        assert_eq!(replay.frames.len(), RECORDED_FRAMES);
+        (replay, player_translation(app.world_mut()))
+    }
+
+    // the harness scripts its own input from the first frame, so this plays the recording as the replay menu would
+    fn play_back(replay: &Replay) -> App {
+        let mut app = headless_app();
+        app.update();
+        let recording = replay.clone();
+        app.world_mut().run_system_once(move |
+            mut commands: Commands,
+            mut chosen_seed: ResMut<ChosenSeed>,
+            mut chosen_level: ResMut<ChosenLevel>,
+            mut game_state: ResMut<NextState<GameState>>,
+        | {
+            start_replay_playback(&mut commands, recording.clone(), &mut chosen_seed, &mut chosen_level);
+            game_state.set(GameState::StartingGame);
+        }).unwrap();
+        for _ in 0..2 * replay.frames.len() {
+            if replay_frame(app.world()).is_some_and(|frame| frame >= replay.frames.len()) {
+                break;
+            }
+            app.update();
+        }
+        app
+    }
+
+    #[test]
+    fn playing_back_a_recording_reproduces_the_run() {
+        let (replay, recorded_translation) = record_run(3);
+        let mut app = play_back(&replay);
+
+        assert!(!replay.checksums.is_empty());
+        assert_eq!(app.world().resource::<ReplayPlayback>().frame, replay.frames.len());
+        assert_eq!(app.world().resource::<ReplayPlayback>().desync_frame, None);
+        assert_eq!(player_translation(app.world_mut()), recorded_translation);
+    }
+
+    #[test]
+    fn a_checksum_that_doesnt_match_the_run_is_caught_as_a_desync() {
+        let (mut replay, _) = record_run(3);
+        let (frame, checksum) = &mut replay.checksums[1];
+        *checksum ^= 1;
+        let frame = *frame;
+        let app = play_back(&replay);
+
+        assert_eq!(app.world().resource::<ReplayPlayback>().desync_frame, Some(frame));
+    }
+
+    #[test]
+    fn replays_from_another_version_are_rejected() {
+        let mut bytes = Replay::new(header()).encode();
+        bytes[REPLAY_MAGIC.len()] = REPLAY_VERSION + 1;
+
+        assert!(Replay::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn replays_saved_at_the_same_moment_get_their_own_files() {
+        let replay = Replay::new(header());
+        let first = replay.save().unwrap();
+        let second = replay.save().unwrap();
+
+        assert_ne!(first, second);
+        assert!(first.is_file() && second.is_file());
+        for path in [first, second] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn truncated_replays_are_rejected() {
+        let bytes = Replay::new(header()).encode();
+
+        assert!(Replay::decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Replay::decode(b"nope").is_err());
+    }
+
+    #[test]
+    fn a_stage_reached_through_a_handoff_records_it_in_the_replay() {
+        let handoff = StageHandoff {
+            lives: 1,
+            bombs: 5,
+            power: 4,
+            graze: 12,
+            score: 67_890,
+            continues: 0,
+        };
+        let mut app = headless_app();
+        app.update();
+        app.world_mut().resource_mut::<ChosenLevel>().level = LevelState::Level1;
+        app.world_mut().insert_resource(handoff.clone());
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::StartingGame);
+        for _ in 0..MAX_STARTUP_FRAMES {
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<ReplayRecording>().0.header.handoff, Some(handoff.clone()));
+        assert_eq!(StageHandoff::new(app.world().resource::<PlayerStats>()), handoff);
+    }
+}
@@ -1,40 +1,162 @@
+use std::collections::HashMap;
 use bevy::asset::{AssetServer, Handle};
-use bevy::audio::{AudioPlayer, AudioSource, GlobalVolume, PlaybackSettings, Volume};
-use bevy::prelude::{Commands, Event, EventReader, Res, ResMut, Resource};
+use bevy::audio::{AudioPlayer, AudioSource, PlaybackSettings, Volume};
+use bevy::prelude::{Commands, Component, Event, EventReader, EventWriter, Query, Res, ResMut, Resource};
+use crate::bosses::boss_health_bar::BossDamageEvent;
+use crate::bullet::BulletSpawnEvent;
+use crate::enemy::EnemyDeathEvent;
+use crate::player::{GrazeEvent, PlayerDeathEvent};
+use crate::settings::Settings;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SoundEffect {
-    MenuSelect
+    MenuSelect,
+    PlayerShot,
+    EnemyHit,
+    EnemyDeath,
+    PlayerDeath,
+    Graze,
+    Bomb,
+    SpellDeclare,
+    TimerWarning,
+    BulletFire,
+}
+
+impl SoundEffect {
+    pub const ALL: [SoundEffect; 10] = [
+        SoundEffect::MenuSelect,
+        SoundEffect::PlayerShot,
+        SoundEffect::EnemyHit,
+        SoundEffect::EnemyDeath,
+        SoundEffect::PlayerDeath,
+        SoundEffect::Graze,
+        SoundEffect::Bomb,
+        SoundEffect::SpellDeclare,
+        SoundEffect::TimerWarning,
+        SoundEffect::BulletFire,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            SoundEffect::MenuSelect => "sfx/se_select00.wav",
+            SoundEffect::PlayerShot => "sfx/se_plst00.wav",
+            SoundEffect::EnemyHit => "sfx/se_damage00.wav",
+            SoundEffect::EnemyDeath => "sfx/se_enep00.wav",
+            SoundEffect::PlayerDeath => "sfx/se_pldead00.wav",
+            SoundEffect::Graze => "sfx/se_graze.wav",
+            SoundEffect::Bomb => "sfx/se_nep00.wav",
+            SoundEffect::SpellDeclare => "sfx/se_cat00.wav",
+            SoundEffect::TimerWarning => "sfx/se_timeout.wav",
+            SoundEffect::BulletFire => "sfx/se_tan00.wav",
+        }
+    }
+
+    // how many copies can overlap before new ones are dropped
+    fn max_voices(self) -> usize {
+        match self {
+            SoundEffect::PlayerShot | SoundEffect::EnemyHit | SoundEffect::BulletFire => 2,
+            // holding a direction in a menu repeats faster than the sound can finish
+            SoundEffect::Graze | SoundEffect::EnemyDeath | SoundEffect::MenuSelect => 3,
+            _ => 1,
+        }
+    }
 }
 
 #[derive(Event)]
 pub struct PlaySoundEvent(pub SoundEffect);
 
 #[derive(Resource, Default)]
-pub struct Sounds {
-    pub menu_select: Handle<AudioSource>
-}
+pub struct Sounds(HashMap<SoundEffect, Handle<AudioSource>>);
+
+// a playing sound, counted against its effect's voice limit until it despawns
+#[derive(Component)]
+pub struct SoundEffectVoice(SoundEffect);
 
 pub fn load_sounds(
-    mut volume: ResMut<GlobalVolume>,
     mut sounds: ResMut<Sounds>, asset_server: Res<AssetServer>
 ) {
-    volume.volume = Volume::new(0.2);
-    sounds.menu_select = asset_server.load("sfx/se_select00.wav");
+    for effect in SoundEffect::ALL {
+        sounds.0.insert(effect, asset_server.load(effect.path()));
+    }
+}
+
+// turns what happened in the simulation into sounds, leaving the systems that send these events alone
+pub fn play_gameplay_sounds(
+    mut graze_event_reader: EventReader<GrazeEvent>,
+    mut player_death_event_reader: EventReader<PlayerDeathEvent>,
+    mut enemy_death_event_reader: EventReader<EnemyDeathEvent>,
+    mut boss_damage_event_reader: EventReader<BossDamageEvent>,
+    mut bullet_spawn_event_reader: EventReader<BulletSpawnEvent>,
+    mut play_sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    let sounds = [
+        (graze_event_reader.read().count(), SoundEffect::Graze),
+        (player_death_event_reader.read().count(), SoundEffect::PlayerDeath),
+        (enemy_death_event_reader.read().count(), SoundEffect::EnemyDeath),
+        (boss_damage_event_reader.read().count(), SoundEffect::EnemyHit),
+        (bullet_spawn_event_reader.read().count(), SoundEffect::BulletFire),
+    ];
+    for (count, effect) in sounds {
+        if count > 0 {
+            play_sound_event_writer.send(PlaySoundEvent(effect));
+        }
+    }
 }
 
 pub fn listen_for_play_sound_events(
     mut commands: Commands,
     mut play_sound_event_reader: EventReader<PlaySoundEvent>,
     sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    voice_query: Query<&SoundEffectVoice>,
 ) {
-    for play_sound_event in play_sound_event_reader.read() {
-        match play_sound_event.0 {
-            SoundEffect::MenuSelect => {
-                commands.spawn((
-                    AudioPlayer::new(sounds.menu_select.clone()),
-                    PlaybackSettings::DESPAWN,
-                ));
-            }
+    let mut playing = HashMap::new();
+    for voice in voice_query.iter() {
+        *playing.entry(voice.0).or_insert(0) += 1;
+    }
+    let requested = play_sound_event_reader.read().map(|event| event.0);
+    let playback_settings = PlaybackSettings::DESPAWN.with_volume(Volume::new(settings.sfx_volume()));
+    for effect in sounds_to_start(requested, &playing) {
+        let Some(handle) = sounds.0.get(&effect) else {
+            continue;
+        };
+        commands.spawn((
+            AudioPlayer::new(handle.clone()),
+            playback_settings,
+            SoundEffectVoice(effect),
+        ));
+    }
+}
+
+// each effect starts at most once a frame, and not at all while it's already at its voice limit
+fn sounds_to_start(requested: impl IntoIterator<Item = SoundEffect>, playing: &HashMap<SoundEffect, usize>) -> Vec<SoundEffect> {
+    let mut effects = Vec::new();
+    for effect in requested {
+        let voices = playing.get(&effect).copied().unwrap_or(0);
+        if !effects.contains(&effect) && voices < effect.max_voices() {
+            effects.push(effect);
+        }
+    }
+    effects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_sounds_in_a_frame_play_once_unless_out_of_voices() {
+        let requested = [SoundEffect::BulletFire; 300].into_iter()
+            .chain([SoundEffect::Graze, SoundEffect::MenuSelect, SoundEffect::Graze]);
+        let playing = HashMap::from([(SoundEffect::MenuSelect, 3), (SoundEffect::Graze, 2)]);
+
+        assert_eq!(sounds_to_start(requested, &playing), vec![SoundEffect::BulletFire, SoundEffect::Graze]);
+    }
+
+    #[test]
+    fn every_sound_effect_is_in_the_assets() {
+        for effect in SoundEffect::ALL {
+            assert!(std::path::Path::new("assets").join(effect.path()).is_file(), "{} is missing", effect.path());
         }
     }
-}
\ No newline at end of file
+}
@@ -31,6 +31,7 @@ pub struct Sprites {
     pub frame: Sprite,
     pub player_spell_text: Sprite,
     pub life_counter: Sprite,
+    pub bomb_counter: Sprite,
     pub blue_fang_shot: Sprite,
 
     pub remilia: AnimatedSprite,
@@ -111,6 +112,11 @@ pub fn load_sprites(
         rect: Option::from(Rect::new(368.0, 98.0, 383.0, 113.0)),
         ..Default::default()
     };
+    sprites.bomb_counter = Sprite {
+        image: images.sidebar.clone(),
+        rect: Option::from(Rect::new(384.0, 98.0, 399.0, 113.0)),
+        ..Default::default()
+    };
     sprites.blue_fang_shot = Sprite {
         image: images.player_accessories.clone(),
         color: Color::srgba(0.8, 0.8, 1.0, 0.5),
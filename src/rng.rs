@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const COSMETIC_STREAM: u64 = 1;
+
+// gameplay randomness and visual-only randomness come from separate streams of the same seed,
+// so tweaking an effect never changes where the bullets go
+#[derive(Resource, Clone)]
+pub struct GameRng {
+    pub seed: u64,
+    pub gameplay: ChaCha8Rng,
+    pub cosmetic: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        let mut cosmetic = ChaCha8Rng::seed_from_u64(seed);
+        cosmetic.set_stream(COSMETIC_STREAM);
+        Self {
+            seed,
+            gameplay: ChaCha8Rng::seed_from_u64(seed),
+            cosmetic,
+        }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ChosenSeed(pub Option<u64>);
+
+pub fn reseed_game_rng(
+    mut game_rng: ResMut<GameRng>,
+    chosen_seed: Res<ChosenSeed>,
+) {
+    let seed = chosen_seed.0.unwrap_or_else(|| rand::rng().random());
+    *game_rng = GameRng::from_seed(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bullet::Bullet;
+    use crate::game::LevelState;
+    use crate::headless::{holding, Harness, SECONDS};
+    use crate::input::GameButton;
+    use crate::player::Player;
+
+    #[test]
+    fn runs_with_the_same_seed_and_input_play_out_identically() {
+        let mut first = Harness::new(LevelState::Level1, 42);
+        let mut second = Harness::new(LevelState::Level1, 42);
+        for harness in [&mut first, &mut second] {
+            harness.hold(holding(GameButton::Shot));
+            harness.step(8 * SECONDS);
+        }
+
+        assert!(!first.positions::<Bullet>().is_empty());
+        assert_eq!(first.positions::<Bullet>(), second.positions::<Bullet>());
+        assert_eq!(first.count::<Player>(), second.count::<Player>());
+    }
+}
@@ -0,0 +1,302 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowResolution};
+use crate::input::{binding_name, parse_binding, Action, KeyBindings, PadBindings, BINDABLE_BUTTONS, BINDABLE_KEYS};
+use crate::player_stats::STARTING_LIFE_COUNT;
+use crate::practice::MAX_PRACTICE_LIVES;
+use crate::DEFAULT_RESOLUTION;
+
+pub const CONFIG_FILE_NAME: &str = "config.txt";
+pub const MAX_VOLUME_LEVEL: u32 = 10;
+pub const MIN_WINDOW_SCALE: u32 = 1;
+pub const MAX_WINDOW_SCALE: u32 = 4;
+pub const MIN_DEADZONE_PERCENT: u32 = 5;
+pub const MAX_DEADZONE_PERCENT: u32 = 90;
+const DEADZONE_STEP: u32 = 5;
+// the loudest a volume level plays at, so the default level matches the old fixed volume
+const FULL_VOLUME: f32 = 0.4;
+
+// everything on the options screen, saved to the config file whenever it changes
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub bgm_volume: u32,
+    pub sfx_volume: u32,
+    pub window_scale: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub practice_lives: usize,
+    pub key_bindings: KeyBindings,
+    pub pad_bindings: PadBindings,
+    pub deadzone_percent: u32,
+    pub eight_way_stick: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bgm_volume: MAX_VOLUME_LEVEL / 2,
+            sfx_volume: MAX_VOLUME_LEVEL / 2,
+            window_scale: 2,
+            fullscreen: false,
+            vsync: true,
+            practice_lives: STARTING_LIFE_COUNT,
+            key_bindings: KeyBindings::default(),
+            pad_bindings: PadBindings::default(),
+            deadzone_percent: 25,
+            eight_way_stick: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        match fs::read_to_string(data_path(CONFIG_FILE_NAME)) {
+            Ok(text) => Self::decode(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        save_data_file(CONFIG_FILE_NAME, self.encode())
+    }
+
+    pub fn encode(&self) -> String {
+        let mut lines = vec![
+            format!("bgm-volume {}", self.bgm_volume),
+            format!("sfx-volume {}", self.sfx_volume),
+            format!("window-scale {}", self.window_scale),
+            format!("fullscreen {}", on_off(self.fullscreen)),
+            format!("vsync {}", on_off(self.vsync)),
+            format!("practice-lives {}", self.practice_lives),
+            format!("deadzone {}", self.deadzone_percent),
+            format!("eight-way-stick {}", on_off(self.eight_way_stick)),
+        ];
+        for action in Action::ALL {
+            lines.push(format!("key {} {}", action.key(), binding_name(self.key_bindings.get(action))));
+        }
+        for action in Action::ALL {
+            lines.push(format!("pad {} {}", action.key(), binding_name(self.pad_bindings.get(action))));
+        }
+        lines.join("\n")
+    }
+
+    // settings missing from the file or on a line that can't be read keep their defaults
+    pub fn decode(text: &str) -> Self {
+        let mut settings = Self::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            if let Err(error) = settings.decode_line(line) {
+                error!("Could not read setting: {}", error);
+            }
+        }
+        settings
+    }
+
+    fn decode_line(&mut self, line: &str) -> io::Result<()> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["bgm-volume", level] => self.bgm_volume = parse_field::<u32>(level)?.min(MAX_VOLUME_LEVEL),
+            ["sfx-volume", level] => self.sfx_volume = parse_field::<u32>(level)?.min(MAX_VOLUME_LEVEL),
+            ["window-scale", scale] => self.window_scale = parse_field::<u32>(scale)?.clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE),
+            ["fullscreen", value] => self.fullscreen = parse_on_off(value)?,
+            ["vsync", value] => self.vsync = parse_on_off(value)?,
+            ["practice-lives", lives] => self.practice_lives = parse_field::<usize>(lives)?.min(MAX_PRACTICE_LIVES),
+            ["deadzone", percent] => {
+                self.deadzone_percent = parse_field::<u32>(percent)?.clamp(MIN_DEADZONE_PERCENT, MAX_DEADZONE_PERCENT);
+            },
+            ["eight-way-stick", value] => self.eight_way_stick = parse_on_off(value)?,
+            ["key", action, key] => self.key_bindings.set(parse_action(action)?, parse_bindable(&BINDABLE_KEYS, key)?),
+            ["pad", action, button] => self.pad_bindings.set(parse_action(action)?, parse_bindable(&BINDABLE_BUTTONS, button)?),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unexpected line {}", line))),
+        }
+        Ok(())
+    }
+
+    pub fn bgm_volume(&self) -> f32 {
+        volume(self.bgm_volume)
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        volume(self.sfx_volume)
+    }
+
+    pub fn change_bgm_volume(&mut self, delta: i32) {
+        self.bgm_volume = self.bgm_volume.saturating_add_signed(delta).min(MAX_VOLUME_LEVEL);
+    }
+
+    pub fn change_sfx_volume(&mut self, delta: i32) {
+        self.sfx_volume = self.sfx_volume.saturating_add_signed(delta).min(MAX_VOLUME_LEVEL);
+    }
+
+    pub fn change_window_scale(&mut self, delta: i32) {
+        self.window_scale = self.window_scale.saturating_add_signed(delta).clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE);
+    }
+
+    pub fn stick_deadzone(&self) -> f32 {
+        self.deadzone_percent as f32 / 100.0
+    }
+
+    pub fn change_deadzone(&mut self, delta: i32) {
+        self.deadzone_percent = self.deadzone_percent.saturating_add_signed(delta * DEADZONE_STEP as i32)
+            .clamp(MIN_DEADZONE_PERCENT, MAX_DEADZONE_PERCENT);
+    }
+
+    pub fn change_practice_lives(&mut self, delta: i32) {
+        self.practice_lives = self.practice_lives.saturating_add_signed(delta as isize).min(MAX_PRACTICE_LIVES);
+    }
+
+    pub fn window_resolution(&self, scale: f32) -> WindowResolution {
+        WindowResolution::new(DEFAULT_RESOLUTION.x * scale, DEFAULT_RESOLUTION.y * scale)
+            .with_scale_factor_override(scale)
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+fn volume(level: u32) -> f32 {
+    level as f32 / MAX_VOLUME_LEVEL as f32 * FULL_VOLUME
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+fn parse_on_off(field: &str) -> io::Result<bool> {
+    match field {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("expected on or off, got {}", field))),
+    }
+}
+
+fn parse_action(field: &str) -> io::Result<Action> {
+    Action::ALL.into_iter().find(|action| action.key() == field)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown action {}", field)))
+}
+
+fn parse_bindable<T: Copy + std::fmt::Debug>(bindable: &[T], field: &str) -> io::Result<T> {
+    parse_binding(bindable, field).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown binding {}", field)))
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str) -> io::Result<T> {
+    field.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid number {}", field)))
+}
+
+// where settings and other saved files live: the platform's per-user config directory, or the
+// working directory if it can't be found. tests use a temp directory so they never touch real saves
+pub fn data_path(file_name: &str) -> PathBuf {
+    let base = if cfg!(test) {
+        Some(env::temp_dir().join("shmup-test"))
+    } else if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|directory| directory.join("shmup")).unwrap_or_default().join(file_name)
+}
+
+pub fn save_data_file(file_name: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = data_path(file_name);
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, contents)
+}
+
+pub fn settings_plugin(app: &mut App) {
+    app
+        .init_resource::<Settings>()
+        .add_systems(Update, (
+            apply_window_settings,
+            save_settings,
+        ).run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))))
+    ;
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in window_query.iter_mut() {
+        let resolution = settings.window_resolution(settings.window_scale as f32);
+        if window.resolution != resolution {
+            window.resolution = resolution;
+        }
+        window.mode = settings.window_mode();
+        window.present_mode = settings.present_mode();
+    }
+}
+
+fn save_settings(
+    settings: Res<Settings>,
+) {
+    if let Err(error) = settings.save() {
+        error!("Could not save settings: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_survive_an_encode_decode_round_trip() {
+        let mut key_bindings = KeyBindings::default();
+        assert!(key_bindings.bind(Action::Bomb, KeyCode::KeyC));
+        assert!(key_bindings.bind(Action::Pause, KeyCode::KeyP));
+        let mut pad_bindings = PadBindings::default();
+        assert!(pad_bindings.bind(Action::Focus, GamepadButton::LeftTrigger2));
+        let settings = Settings {
+            bgm_volume: 3,
+            sfx_volume: 10,
+            window_scale: 4,
+            fullscreen: true,
+            vsync: false,
+            practice_lives: 7,
+            key_bindings,
+            pad_bindings,
+            deadzone_percent: 40,
+            eight_way_stick: true,
+        };
+
+        assert_eq!(Settings::decode(&settings.encode()), settings);
+        assert_eq!(Settings::decode("sfx-volume 1"), Settings { sfx_volume: 1, ..default() });
+    }
+
+    #[test]
+    fn malformed_settings_are_rejected_and_out_of_range_ones_clamped() {
+        let mut settings = Settings::default();
+        assert!(settings.decode_line("vsync maybe").is_err());
+        assert!(settings.decode_line("brightness 3").is_err());
+        assert!(settings.decode_line("key shot Mouse1").is_err());
+        assert!(settings.decode_line("pad jump South").is_err());
+        assert_eq!(settings, Settings::default());
+        assert_eq!(Settings::decode("window-scale 9").window_scale, MAX_WINDOW_SCALE);
+    }
+
+    #[test]
+    fn a_malformed_line_only_loses_its_own_setting() {
+        let settings = Settings::decode("bgm-volume 2\nsfx-volume loud\nfullscreen on\nwindow-scale");
+
+        assert_eq!(settings, Settings { bgm_volume: 2, fullscreen: true, ..default() });
+    }
+}
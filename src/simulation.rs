@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use crate::bosses::boss::Boss;
+use crate::bullet::Bullet;
+use crate::enemy::Enemy;
+use crate::player::{Player, PlayerShot};
+use crate::snapshot::snapshot_component;
+use crate::GameState;
+
+pub const SIMULATION_HZ: f64 = 60.0;
+
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    Input,
+    Actors,
+    Stage,
+}
+
+// the translation as of the last two simulation ticks, used to draw in between them
+#[derive(Component, Clone)]
+pub struct SimulatedTranslation {
+    previous: Vec3,
+    current: Vec3,
+}
+
+type Simulated = Or<(With<Player>, With<PlayerShot>, With<Enemy>, With<Boss>, With<Bullet>)>;
+
+pub fn simulation_plugin(app: &mut App) {
+    app
+        .insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ))
+        .configure_sets(FixedUpdate, (SimulationSet::Input, SimulationSet::Actors, SimulationSet::Stage).chain())
+        .configure_sets(FixedUpdate, SimulationSet::Input.run_if(in_state(GameState::PlayingGame)))
+        .configure_sets(FixedUpdate, SimulationSet::Actors.run_if(in_state(GameState::PlayingGame)))
+        .configure_sets(FixedUpdate, SimulationSet::Stage.run_if(in_state(GameState::PlayingGame)))
+        .add_systems(RunFixedMainLoop, (
+            restore_simulated_translations.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+            interpolate_simulated_translations.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+        ))
+        .add_systems(FixedFirst, run_state_transitions)
+        .add_systems(FixedPostUpdate, record_simulated_translations)
+        .add_plugins(snapshot_component::<SimulatedTranslation>)
+    ;
+}
+
+// applies state changes every tick rather than once per frame, so a run plays out the same
+// no matter how many ticks share a frame
+fn run_state_transitions(world: &mut World) {
+    world.run_schedule(StateTransition);
+}
+
+fn restore_simulated_translations(
+    mut query: Query<(&mut Transform, &SimulatedTranslation)>,
+) {
+    for (mut transform, simulated) in query.iter_mut() {
+        transform.translation = simulated.current;
+    }
+}
+
+pub fn record_simulated_translations(
+    mut commands: Commands,
+    mut query: Query<(&Transform, Option<&mut SimulatedTranslation>, Entity), Simulated>,
+) {
+    for (transform, simulated, entity) in query.iter_mut() {
+        match simulated {
+            Some(mut simulated) => {
+                simulated.previous = simulated.current;
+                simulated.current = transform.translation;
+            }
+            None => {
+                commands.entity(entity).try_insert(SimulatedTranslation {
+                    previous: transform.translation,
+                    current: transform.translation,
+                });
+            }
+        }
+    }
+}
+
+fn interpolate_simulated_translations(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &SimulatedTranslation)>,
+) {
+    let overstep = fixed_time.overstep_fraction();
+    for (mut transform, simulated) in query.iter_mut() {
+        transform.translation = simulated.previous.lerp(simulated.current, overstep);
+    }
+}
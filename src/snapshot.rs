@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::state::state::FreelyMutableState;
+use crate::bosses::boss::{Boss, BossSpawner};
+use crate::bosses::boss_health_bar::{BossHealthBar, BossHealthBarBundle, SpellTimeLimit};
+use crate::bullet::{Bullet, BulletSpawner};
+use crate::bullet_patterns::BulletPattern;
+use crate::enemy::{Enemy, EnemySpawner};
+use crate::game::{GameObject, SpawnTimer, StageClock};
+use crate::input::PlayerInput;
+use crate::laser::{Laser, LaserSegment};
+use crate::movement_patterns::MovementPatterns;
+use crate::player::{Grazed, Player, PlayerBombTimer, PlayerHomingShotTimer, PlayerInvincibilityTimer, PlayerRespawnTimer, PlayerShot, PlayerShotTimer};
+use crate::player_stats::{PlayerBombCounter, PlayerLifeCounter, PlayerStats};
+use crate::replay::{replay_frame, rewind_replay};
+use crate::resources::sprites::{AnimatedSprite, AnimationIndices, AnimationTimer};
+use crate::rng::GameRng;
+use crate::simulation::record_simulated_translations;
+use crate::time_control::{simulation_running, RewindEvent};
+use crate::GameState;
+
+const SNAPSHOT_INTERVAL: usize = 60;
+const MAX_SNAPSHOTS: usize = 8;
+
+type RestoreComponent = Box<dyn Fn(&mut EntityWorldMut) + Send + Sync>;
+type RestoreResource = Box<dyn Fn(&mut World, &EntityHashMap<Entity>) + Send + Sync>;
+
+// how to copy each kind of component and resource a run is made of, filled in by the plugins that own them
+#[derive(Resource, Default)]
+struct SnapshotRegistry {
+    components: Vec<fn(&EntityRef) -> Option<RestoreComponent>>,
+    resources: Vec<fn(&World) -> RestoreResource>,
+}
+
+struct EntitySnapshot {
+    entity: Entity,
+    parent: Option<Entity>,
+    components: Vec<RestoreComponent>,
+}
+
+// every game object and simulation resource as of the end of a tick
+struct Snapshot {
+    frame: usize,
+    entities: Vec<EntitySnapshot>,
+    resources: Vec<RestoreResource>,
+}
+
+#[derive(Resource, Default)]
+struct Snapshots(VecDeque<Snapshot>);
+
+// for resources that point at game objects, which come back under new ids when a snapshot is restored
+pub trait RemapEntities {
+    fn remap_entities(&mut self, entity_map: &EntityHashMap<Entity>);
+}
+
+pub fn snapshot_plugin(app: &mut App) {
+    app
+        .init_resource::<SnapshotRegistry>()
+        .init_resource::<Snapshots>()
+        .add_systems(FixedPostUpdate, take_snapshot
+            .after(record_simulated_translations)
+            .run_if(in_state(GameState::PlayingGame).and(simulation_running)))
+        .add_systems(RunFixedMainLoop, listen_for_rewind
+            .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop)
+            .run_if(in_state(GameState::PlayingGame)))
+        .add_systems(OnEnter(GameState::StartingGame), clear_snapshots)
+        .add_systems(OnEnter(GameState::MainMenu), clear_snapshots)
+        .add_systems(OnEnter(GameState::PracticeMenu), clear_snapshots)
+        .add_plugins((
+            snapshot_component::<Name>,
+            snapshot_component::<Transform>,
+            snapshot_component::<Visibility>,
+            snapshot_component::<Sprite>,
+            snapshot_component::<Anchor>,
+            snapshot_component::<Mesh2d>,
+            snapshot_component::<MeshMaterial2d<ColorMaterial>>,
+            snapshot_component::<Text2d>,
+            snapshot_component::<TextFont>,
+            snapshot_component::<TextColor>,
+            snapshot_component::<TextLayout>,
+        ))
+        .add_plugins((
+            snapshot_component::<GameObject>,
+            snapshot_component::<SpawnTimer>,
+            snapshot_component::<MovementPatterns>,
+            snapshot_component::<AnimatedSprite>,
+            snapshot_component::<AnimationIndices>,
+            snapshot_component::<AnimationTimer>,
+        ))
+        .add_plugins((
+            snapshot_component::<Player>,
+            snapshot_component::<PlayerShot>,
+            snapshot_component::<PlayerShotTimer>,
+            snapshot_component::<PlayerHomingShotTimer>,
+            snapshot_component::<PlayerRespawnTimer>,
+            snapshot_component::<PlayerInvincibilityTimer>,
+            snapshot_component::<PlayerLifeCounter>,
+            snapshot_component::<Grazed>,
+            snapshot_component::<PlayerBombTimer>,
+            snapshot_component::<PlayerBombCounter>,
+        ))
+        .add_plugins((
+            snapshot_component::<Enemy>,
+            snapshot_component::<EnemySpawner>,
+            snapshot_component::<Boss>,
+            snapshot_component::<BossSpawner>,
+            snapshot_component::<BossHealthBar>,
+            snapshot_component::<BossHealthBarBundle>,
+            snapshot_component::<SpellTimeLimit>,
+            snapshot_component::<Bullet>,
+            snapshot_component::<BulletSpawner>,
+            snapshot_component::<BulletPattern>,
+            snapshot_component::<Laser>,
+            snapshot_component::<LaserSegment>,
+        ))
+        .add_plugins((
+            snapshot_resource::<GameRng>,
+            snapshot_resource::<PlayerInput>,
+            snapshot_resource::<StageClock>,
+            snapshot_resource_with_entities::<PlayerStats>,
+        ))
+    ;
+}
+
+pub fn snapshot_component<C: Component + Clone>(app: &mut App) {
+    app.init_resource::<SnapshotRegistry>();
+    app.world_mut().resource_mut::<SnapshotRegistry>().components.push(capture_component::<C>);
+}
+
+pub fn snapshot_resource<R: Resource + Clone>(app: &mut App) {
+    app.init_resource::<SnapshotRegistry>();
+    app.world_mut().resource_mut::<SnapshotRegistry>().resources.push(capture_resource::<R>);
+}
+
+pub fn snapshot_resource_with_entities<R: Resource + Clone + RemapEntities>(app: &mut App) {
+    app.init_resource::<SnapshotRegistry>();
+    app.world_mut().resource_mut::<SnapshotRegistry>().resources.push(capture_resource_with_entities::<R>);
+}
+
+// restoring a state sets it directly, so none of its OnEnter or OnExit setup runs again
+pub fn snapshot_state<S: FreelyMutableState>(app: &mut App) {
+    app.init_resource::<SnapshotRegistry>();
+    app.world_mut().resource_mut::<SnapshotRegistry>().resources.push(capture_state::<S>);
+}
+
+fn capture_component<C: Component + Clone>(entity: &EntityRef) -> Option<RestoreComponent> {
+    let component = entity.get::<C>()?.clone();
+    Some(Box::new(move |entity: &mut EntityWorldMut| {
+        entity.insert(component.clone());
+    }))
+}
+
+fn capture_resource<R: Resource + Clone>(world: &World) -> RestoreResource {
+    let resource = world.resource::<R>().clone();
+    Box::new(move |world: &mut World, _: &EntityHashMap<Entity>| {
+        world.insert_resource(resource.clone());
+    })
+}
+
+fn capture_resource_with_entities<R: Resource + Clone + RemapEntities>(world: &World) -> RestoreResource {
+    let resource = world.resource::<R>().clone();
+    Box::new(move |world: &mut World, entity_map: &EntityHashMap<Entity>| {
+        let mut resource = resource.clone();
+        resource.remap_entities(entity_map);
+        world.insert_resource(resource);
+    })
+}
+
+fn capture_state<S: FreelyMutableState>(world: &World) -> RestoreResource {
+    let state = world.resource::<State<S>>().get().clone();
+    let next_state = world.resource::<NextState<S>>().clone();
+    Box::new(move |world: &mut World, _: &EntityHashMap<Entity>| {
+        world.insert_resource(State::new(state.clone()));
+        world.insert_resource(next_state.clone());
+    })
+}
+
+fn take_snapshot(world: &mut World) {
+    let Some(frame) = replay_frame(world) else {
+        return;
+    };
+    let snapshots = world.resource::<Snapshots>();
+    if frame % SNAPSHOT_INTERVAL != 0 || snapshots.0.back().is_some_and(|snapshot| snapshot.frame >= frame) {
+        return;
+    }
+
+    // children are kept right after their parent, so they can be attached again on restore
+    let mut pending: Vec<(Entity, Option<Entity>)> = world
+        .query_filtered::<Entity, (With<GameObject>, Without<Parent>)>()
+        .iter(world)
+        .map(|entity| (entity, None))
+        .collect();
+    pending.reverse();
+    let registry = world.resource::<SnapshotRegistry>();
+    let mut entities = Vec::new();
+    while let Some((entity, parent)) = pending.pop() {
+        let entity_ref = world.entity(entity);
+        if let Some(children) = entity_ref.get::<Children>() {
+            pending.extend(children.iter().rev().map(|child| (*child, Some(entity))));
+        }
+        entities.push(EntitySnapshot {
+            entity,
+            parent,
+            components: registry.components.iter().filter_map(|capture| capture(&entity_ref)).collect(),
+        });
+    }
+    let resources = registry.resources.iter().map(|capture| capture(world)).collect();
+
+    let mut snapshots = world.resource_mut::<Snapshots>();
+    snapshots.0.push_back(Snapshot { frame, entities, resources });
+    if snapshots.0.len() > MAX_SNAPSHOTS {
+        snapshots.0.pop_front();
+    }
+}
+
+fn restore_snapshot(world: &mut World, snapshot: &Snapshot) {
+    let game_objects: Vec<Entity> = world.query_filtered::<Entity, With<GameObject>>().iter(world).collect();
+    for entity in game_objects {
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+
+    let mut entity_map = EntityHashMap::default();
+    for entity_snapshot in &snapshot.entities {
+        let mut entity = world.spawn_empty();
+        for restore in &entity_snapshot.components {
+            restore(&mut entity);
+        }
+        entity_map.insert(entity_snapshot.entity, entity.id());
+    }
+    for entity_snapshot in &snapshot.entities {
+        if let Some(parent) = entity_snapshot.parent {
+            world.entity_mut(entity_map[&parent]).add_child(entity_map[&entity_snapshot.entity]);
+        }
+    }
+    for restore in &snapshot.resources {
+        restore(world, &entity_map);
+    }
+}
+
+// puts back the latest snapshot from at or before the asked-for frame, or the oldest one kept if there is none,
+// before this frame's ticks run so the run carries on straight from there
+fn listen_for_rewind(world: &mut World) {
+    let Some(rewind) = world.resource_mut::<Events<RewindEvent>>().drain().last() else {
+        return;
+    };
+    let Some(frame) = replay_frame(world) else {
+        return;
+    };
+    let target = frame.saturating_sub(rewind.frames);
+    world.resource_scope(|world, mut snapshots: Mut<Snapshots>| {
+        let Some(index) = snapshots.0.iter().rposition(|snapshot| snapshot.frame <= target)
+            .or((!snapshots.0.is_empty()).then_some(0)) else {
+            return;
+        };
+        snapshots.0.truncate(index + 1);
+        let snapshot = &snapshots.0[index];
+        restore_snapshot(world, snapshot);
+        rewind_replay(world, snapshot.frame);
+    });
+}
+
+fn clear_snapshots(mut snapshots: ResMut<Snapshots>) {
+    snapshots.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::LevelState;
+    use crate::headless::{holding, Harness, SECONDS};
+    use crate::input::GameButton;
+
+    #[test]
+    fn rewinding_plays_the_run_back_to_where_it_was() {
+        let mut rewound = Harness::new(LevelState::Level1, 7);
+        rewound.hold(holding(GameButton::Shot));
+        rewound.step(5 * SECONDS);
+        rewound.rewind(2 * SECONDS);
+
+        let mut straight = Harness::new(LevelState::Level1, 7);
+        straight.hold(holding(GameButton::Shot));
+        while straight.frame() < rewound.frame() {
+            straight.step(1);
+        }
+
+        assert!(rewound.frame() < 5 * SECONDS);
+        assert_eq!(rewound.frame(), straight.frame());
+        assert_eq!(rewound.positions::<Bullet>(), straight.positions::<Bullet>());
+    }
+}
@@ -0,0 +1,241 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::game::{Checkpoint, ChosenLevel, StageClearEvent, StageClock};
+use crate::input::Action;
+use crate::player_stats::{PlayerStats, StageHandoff};
+use crate::practice::PracticeSession;
+use crate::simulation::SimulationSet;
+use crate::{despawn_screen, GameState};
+
+const KILL_BONUS: u64 = 500;
+const LIFE_BONUS: u64 = 20_000;
+// every second under par is worth this much
+const TIME_BONUS: u64 = 1_000;
+const PAR_TIME: Duration = Duration::from_secs(180);
+// the screens ignore input for a moment so a held shot button doesn't skip them
+const SKIP_DELAY: Duration = Duration::from_secs(1);
+const RESULTS_DURATION: Duration = Duration::from_secs(8);
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StageBonus {
+    pub kills: u64,
+    pub time: u64,
+    pub lives: u64,
+}
+
+impl StageBonus {
+    pub fn new(kills: u32, clear_time: Duration, lives: usize) -> Self {
+        Self {
+            kills: kills as u64 * KILL_BONUS,
+            time: PAR_TIME.saturating_sub(clear_time).as_secs() * TIME_BONUS,
+            lives: lives as u64 * LIFE_BONUS,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.kills + self.time + self.lives
+    }
+}
+
+// what the results screen shows, from the moment the stage was cleared
+#[derive(Resource)]
+struct StageResults {
+    kills: u32,
+    clear_time: Duration,
+    lives: usize,
+    bonus: StageBonus,
+}
+
+#[derive(Component)]
+struct OnStageClearScreen;
+
+#[derive(Component)]
+struct OnEndingScreen;
+
+#[derive(Component)]
+struct ScreenTimer(Timer);
+
+pub fn stage_clear_plugin(app: &mut App) {
+    app
+        // practice runs go back to the practice menu instead
+        .add_systems(FixedUpdate, tally_stage_bonus
+            .after(SimulationSet::Stage)
+            .run_if(in_state(GameState::PlayingGame).and(not(resource_exists::<PracticeSession>))))
+        .add_systems(OnEnter(GameState::StageClear), stage_clear_setup)
+        .add_systems(Update, leave_stage_clear.run_if(in_state(GameState::StageClear)))
+        .add_systems(OnExit(GameState::StageClear), despawn_screen::<OnStageClearScreen>)
+        .add_systems(OnEnter(GameState::Ending), ending_setup)
+        .add_systems(Update, leave_ending.run_if(in_state(GameState::Ending)))
+        .add_systems(OnExit(GameState::Ending), despawn_screen::<OnEndingScreen>)
+    ;
+}
+
+fn tally_stage_bonus(
+    mut commands: Commands,
+    mut stage_clear_event_reader: EventReader<StageClearEvent>,
+    mut player_stats: ResMut<PlayerStats>,
+    stage_clock: Res<StageClock>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if stage_clear_event_reader.read().count() == 0 {
+        return;
+    }
+    let lives = player_stats.lives.len();
+    let bonus = StageBonus::new(player_stats.stage_kills, stage_clock.0, lives);
+    player_stats.score += bonus.total();
+    commands.insert_resource(StageResults {
+        kills: player_stats.stage_kills,
+        clear_time: stage_clock.0,
+        lives,
+        bonus,
+    });
+    game_state.set(GameState::StageClear);
+}
+
+fn stage_clear_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    results: Res<StageResults>,
+    player_stats: Res<PlayerStats>,
+) {
+    let frame_center = Vec2::new(-128.0, 4.0);
+    commands.spawn((
+        Name::new("Stage Clear Shadow"),
+        Mesh2d(meshes.add(Rectangle::new(300.0, 260.0))),
+        MeshMaterial2d(materials.add(Color::srgba(0.0, 0.0, 0.0, 0.75))),
+        Transform::from_xyz(frame_center.x, frame_center.y, 0.98),
+        OnStageClearScreen,
+    ));
+    let seconds = results.clear_time.as_secs();
+    commands.spawn((
+        Name::new("StageClearText"),
+        Text2d::new(format!(
+            "Stage Clear!\n\n\
+            Kills  {} x {} = {}\n\
+            Time  {:02}:{:02} = {}\n\
+            Lives  {} x {} = {}\n\n\
+            Bonus  {}\n\
+            Score  {}",
+            results.kills, KILL_BONUS, results.bonus.kills,
+            seconds / 60, seconds % 60, results.bonus.time,
+            results.lives, LIFE_BONUS, results.bonus.lives,
+            results.bonus.total(),
+            player_stats.score,
+        )),
+        TextFont {
+            font: asset_server.load("fonts/Super-Cartoon.ttf"),
+            font_size: 20.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(frame_center.x, frame_center.y, 0.99),
+        TextColor(TEXT_COLOR),
+        ScreenTimer(Timer::new(RESULTS_DURATION, TimerMode::Once)),
+        OnStageClearScreen,
+    ));
+    commands.remove_resource::<StageResults>();
+}
+
+// moves on to the next stage with the player's stats intact, or to the ending after the last one
+fn leave_stage_clear(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    actions: Res<ButtonInput<Action>>,
+    player_stats: Res<PlayerStats>,
+    mut chosen_level: ResMut<ChosenLevel>,
+    mut timer_query: Query<&mut ScreenTimer, With<OnStageClearScreen>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for mut timer in timer_query.iter_mut() {
+        timer.0.tick(time.delta());
+        let skipped = timer.0.elapsed() >= SKIP_DELAY && actions.just_pressed(Action::Confirm);
+        if !timer.0.finished() && !skipped {
+            continue;
+        }
+        match chosen_level.level.next() {
+            Some(next) => {
+                commands.insert_resource(StageHandoff::new(&player_stats));
+                chosen_level.level = next;
+                chosen_level.checkpoint = Checkpoint::Start;
+                game_state.set(GameState::Resetting);
+            },
+            None => game_state.set(GameState::Ending),
+        }
+    }
+}
+
+fn ending_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    player_stats: Res<PlayerStats>,
+) {
+    let frame_center = Vec2::new(-128.0, 4.0);
+    commands.spawn((
+        Name::new("Ending Shadow"),
+        Mesh2d(meshes.add(Rectangle::new(300.0, 260.0))),
+        MeshMaterial2d(materials.add(Color::srgba(0.0, 0.0, 0.0, 0.9))),
+        Transform::from_xyz(frame_center.x, frame_center.y, 0.98),
+        OnEndingScreen,
+    ));
+    let continues = match player_stats.continues {
+        0 => "No continues".to_string(),
+        1 => "1 continue".to_string(),
+        continues => format!("{} continues", continues),
+    };
+    commands.spawn((
+        Name::new("EndingText"),
+        Text2d::new(format!(
+            "All Clear!\n\nThe night is quiet again.\n\nFinal Score  {}\n{}",
+            player_stats.score,
+            continues,
+        )),
+        TextFont {
+            font: asset_server.load("fonts/Super-Cartoon.ttf"),
+            font_size: 22.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(frame_center.x, frame_center.y, 0.99),
+        TextColor(TEXT_COLOR),
+        ScreenTimer(Timer::new(SKIP_DELAY, TimerMode::Once)),
+        OnEndingScreen,
+    ));
+}
+
+fn leave_ending(
+    time: Res<Time<Real>>,
+    actions: Res<ButtonInput<Action>>,
+    mut timer_query: Query<&mut ScreenTimer, With<OnEndingScreen>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for mut timer in timer_query.iter_mut() {
+        if timer.0.tick(time.delta()).finished()
+            && (actions.just_pressed(Action::Confirm) || actions.just_pressed(Action::Cancel))
+        {
+            game_state.set(GameState::MainMenu);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_bonus_counts_kills_time_under_par_and_lives() {
+        let bonus = StageBonus::new(12, PAR_TIME - Duration::from_millis(30_500), 2);
+
+        assert_eq!(bonus, StageBonus {
+            kills: 12 * KILL_BONUS,
+            time: 30 * TIME_BONUS,
+            lives: 2 * LIFE_BONUS,
+        });
+        assert_eq!(bonus.total(), 12 * KILL_BONUS + 30 * TIME_BONUS + 2 * LIFE_BONUS);
+        assert_eq!(StageBonus::new(0, PAR_TIME + Duration::from_secs(10), 0).total(), 0);
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use crate::replay::ReplayPlayback;
+use crate::simulation::{SimulationSet, SIMULATION_HZ};
+use crate::GameState;
+
+pub const SIMULATION_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+pub const NORMAL_SPEED_INDEX: usize = 2;
+#[cfg(debug_assertions)]
+const REWIND_SECONDS: usize = 3;
+
+// debug controls over the simulation itself, separate from the pause menu so the playfield stays visible
+#[derive(Resource)]
+pub struct SimulationControl {
+    pub paused: bool,
+    pending_steps: usize,
+    stepping: bool,
+    speed_index: usize,
+}
+
+impl Default for SimulationControl {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            pending_steps: 0,
+            stepping: false,
+            speed_index: NORMAL_SPEED_INDEX,
+        }
+    }
+}
+
+impl SimulationControl {
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    // runs exactly one more tick while paused
+    pub fn step_frame(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    pub fn faster(&mut self) {
+        self.speed_index = (self.speed_index + 1).min(SIMULATION_SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed_index = self.speed_index.saturating_sub(1);
+    }
+
+    pub fn speed(&self) -> f32 {
+        SIMULATION_SPEEDS[self.speed_index]
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.paused || self.stepping
+    }
+}
+
+// asks for the run to be put back at least the given number of simulation frames, to the nearest snapshot
+#[derive(Event)]
+pub struct RewindEvent {
+    pub frames: usize,
+}
+
+pub fn time_control_plugin(app: &mut App) {
+    app
+        .init_resource::<SimulationControl>()
+        .add_event::<RewindEvent>()
+        .configure_sets(FixedUpdate, (
+            SimulationSet::Input,
+            SimulationSet::Actors,
+            SimulationSet::Stage,
+        ).run_if(simulation_running))
+        .add_systems(FixedFirst, take_frame_step)
+        .add_systems(Update, apply_simulation_speed.run_if(not(resource_exists::<ReplayPlayback>)))
+        .add_systems(OnEnter(GameState::MainMenu), reset_simulation_control)
+        .add_systems(OnEnter(GameState::PracticeMenu), reset_simulation_control)
+    ;
+    #[cfg(debug_assertions)]
+    app.add_systems(Update, debug_time_control_keys.run_if(in_state(GameState::PlayingGame)));
+}
+
+pub fn simulation_running(
+    control: Res<SimulationControl>,
+) -> bool {
+    control.is_running()
+}
+
+fn take_frame_step(
+    mut control: ResMut<SimulationControl>,
+) {
+    control.stepping = control.paused && control.pending_steps > 0;
+    if control.stepping {
+        control.pending_steps -= 1;
+    }
+}
+
+fn apply_simulation_speed(
+    control: Res<SimulationControl>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    time.set_relative_speed(control.speed());
+}
+
+fn reset_simulation_control(
+    mut control: ResMut<SimulationControl>,
+) {
+    *control = SimulationControl::default();
+}
+
+#[cfg(debug_assertions)]
+fn debug_time_control_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut control: ResMut<SimulationControl>,
+    mut rewind_event_writer: EventWriter<RewindEvent>,
+) {
+    if keyboard.just_pressed(KeyCode::F5) {
+        control.toggle_pause();
+    }
+    if keyboard.just_pressed(KeyCode::F6) {
+        control.step_frame();
+    }
+    if keyboard.just_pressed(KeyCode::F7) {
+        control.slower();
+    }
+    if keyboard.just_pressed(KeyCode::F8) {
+        control.faster();
+    }
+    if keyboard.just_pressed(KeyCode::F9) {
+        rewind_event_writer.send(RewindEvent { frames: REWIND_SECONDS * SIMULATION_HZ as usize });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::LevelState;
+    use crate::headless::{Harness, SECONDS};
+
+    #[test]
+    fn a_paused_simulation_only_advances_one_frame_per_step() {
+        let mut harness = Harness::new(LevelState::Level1, 1);
+        harness.step(SECONDS);
+        harness.simulation_control().toggle_pause();
+        harness.step(1);
+        let paused_at = harness.frame();
+
+        harness.step(10);
+        assert_eq!(harness.frame(), paused_at);
+
+        harness.simulation_control().step_frame();
+        harness.step(10);
+        assert_eq!(harness.frame(), paused_at + 1);
+    }
+}